@@ -0,0 +1,264 @@
+// adjust.rs
+//
+// Brightness, contrast, saturation and gamma for the badge picture. They're
+// applied to the screen-sized picture, under any frames and stickers (see
+// `layers.rs`), before it's packed into RGB565, and the preview is decoded back
+// from the packed pixels, so what the Customize Badge screen shows is what gets
+// uploaded. Rendering reads and writes files, so it runs on a blocking thread
+// (see `render`).
+//
+// The badge's TFT panel tends to show midtones lighter and colors paler than a
+// laptop screen; `Adjustments::BADGE_DISPLAY` is a correction for that to start
+// from.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use iced::widget::image;
+use serde::{Deserialize, Serialize};
+
+use crate::layers::{self, Layer};
+use crate::theme_pack::ThemePack;
+use crate::{fwi, image_library};
+
+/// Adjusted pictures kept on disk before the oldest are deleted.
+const MAX_ADJUSTED: usize = 30;
+
+/// The picture, adjustments and layers a render failed for, and why.
+pub type AdjustFailure = (PathBuf, Adjustments, Vec<Layer>, String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Adjustments {
+    /// -1.0 (black) to 1.0 (white); 0.0 leaves the picture alone.
+    pub brightness: f32,
+    /// -1.0 (flat grey) to 1.0.
+    pub contrast: f32,
+    /// -1.0 (greyscale) to 1.0 (twice as colorful).
+    pub saturation: f32,
+    /// 0.5 to 2.0; above 1.0 lightens midtones, below darkens them.
+    pub gamma: f32,
+}
+
+impl Default for Adjustments {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 0.0,
+            saturation: 0.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+impl Adjustments {
+    pub const BADGE_DISPLAY: Adjustments = Adjustments {
+        brightness: 0.0,
+        contrast: 0.1,
+        saturation: 0.25,
+        gamma: 0.8,
+    };
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Adjusts RGBA8 pixels in place. Alpha is left alone.
+    pub fn apply(&self, rgba: &mut [u8]) {
+        // Brightness, contrast and gamma act on each channel alone, so a table does them
+        let table: Vec<f32> = (0..=255)
+            .map(|value| {
+                let value = value as f32 / 255.0 + self.brightness;
+                let value = (value - 0.5) * (1.0 + self.contrast) + 0.5;
+                value.clamp(0.0, 1.0).powf(1.0 / self.gamma)
+            })
+            .collect();
+        let saturation = 1.0 + self.saturation;
+
+        for pixel in rgba.chunks_exact_mut(4) {
+            let [r, g, b] = [
+                table[pixel[0] as usize],
+                table[pixel[1] as usize],
+                table[pixel[2] as usize],
+            ];
+            let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+            for (channel, value) in pixel.iter_mut().zip([r, g, b]) {
+                let value = luma + (value - luma) * saturation;
+                *channel = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        }
+    }
+
+    fn hash_into(&self, hasher: &mut DefaultHasher) {
+        for value in [self.brightness, self.contrast, self.saturation, self.gamma] {
+            value.to_bits().hash(hasher);
+        }
+    }
+}
+
+/// A picture with adjustments and layers applied, ready to upload.
+#[derive(Debug, Clone)]
+pub struct AdjustedPicture {
+    source_path: PathBuf,
+    /// The source fitted to the screen, kept so moving a slider doesn't reload it.
+    base: fwi::FwiImage,
+    adjustments: Adjustments,
+    layers: Vec<Layer>,
+    /// `ThemePack::dir` of the pack the layers were drawn from.
+    pack_dir: Option<PathBuf>,
+    pub fwi_path: PathBuf,
+    pub preview: image::Handle,
+}
+
+impl AdjustedPicture {
+    pub fn is_for(
+        &self,
+        source_path: &Path,
+        adjustments: &Adjustments,
+        layers: &[Layer],
+        pack: &ThemePack,
+    ) -> bool {
+        self.is_of(source_path)
+            && self.adjustments == *adjustments
+            && self.layers == layers
+            && self.pack_dir == pack.dir
+    }
+
+    /// Whether this is `source_path` with some adjustments and layers, if not
+    /// necessarily the current ones.
+    pub fn is_of(&self, source_path: &Path) -> bool {
+        self.source_path == source_path
+    }
+
+    /// Adjusts the picture at `source_path`, a `.fwi` or any picture the `image`
+    /// crate reads, and draws `layers` from `pack` over it. `previous` is reused
+    /// if it's for the same picture.
+    pub fn render(
+        previous: Option<AdjustedPicture>,
+        source_path: &Path,
+        adjustments: Adjustments,
+        layers: &[Layer],
+        pack: &ThemePack,
+    ) -> Result<AdjustedPicture, String> {
+        let base = match previous {
+            Some(previous) if previous.source_path == source_path => previous.base,
+            _ => load_base(source_path)?,
+        };
+
+        let mut adjusted = base.clone();
+        adjustments.apply(&mut adjusted.rgba);
+        layers::flatten(&mut adjusted, layers, pack)?;
+        let bytes = adjusted.encode();
+
+        let dir = image_library::cache_dir().join("adjusted");
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        let mut hasher = DefaultHasher::new();
+        source_path.hash(&mut hasher);
+        adjustments.hash_into(&mut hasher);
+        pack.dir.hash(&mut hasher);
+        layers.hash(&mut hasher);
+        let fwi_path = dir.join(format!("{:016x}.fwi", hasher.finish()));
+        // Rewritten even if present, so the pruning below sees it as the newest
+        fs::write(&fwi_path, &bytes)
+            .map_err(|e| format!("Could not write {}: {}", fwi_path.display(), e))?;
+        prune_adjusted(&dir);
+
+        // Decoded back, so the preview shows the badge's colors rather than the laptop's
+        let preview = fwi::FwiImage::decode(&bytes)?.to_handle();
+        Ok(AdjustedPicture {
+            source_path: source_path.to_path_buf(),
+            base,
+            adjustments,
+            layers: layers.to_vec(),
+            pack_dir: pack.dir.clone(),
+            fwi_path,
+            preview,
+        })
+    }
+}
+
+/// [`AdjustedPicture::render`] on a blocking thread.
+pub async fn render(
+    previous: Option<AdjustedPicture>,
+    source_path: PathBuf,
+    adjustments: Adjustments,
+    layers: Vec<Layer>,
+    pack: Arc<ThemePack>,
+) -> Result<AdjustedPicture, AdjustFailure> {
+    let (failed_path, failed_layers) = (source_path.clone(), layers.clone());
+    tokio::task::spawn_blocking(move || {
+        AdjustedPicture::render(previous, &source_path, adjustments, &layers, &pack)
+    })
+    .await
+    .unwrap_or_else(|e| Err(format!("Adjusting the picture failed: {}", e)))
+    .map_err(|error| (failed_path, adjustments, failed_layers, error))
+}
+
+fn load_base(source_path: &Path) -> Result<fwi::FwiImage, String> {
+    let is_fwi = source_path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("fwi"));
+    if is_fwi {
+        let bytes = fs::read(source_path)
+            .map_err(|e| format!("Could not read {}: {}", source_path.display(), e))?;
+        let decoded = fwi::FwiImage::decode(&bytes)?;
+        if (decoded.width, decoded.height) == (fwi::SCREEN_WIDTH, fwi::SCREEN_HEIGHT) {
+            return Ok(decoded);
+        }
+        // Layers are drawn in screen pixels, so any other size is fitted like a picture
+        let picture = ::image::RgbaImage::from_raw(decoded.width, decoded.height, decoded.rgba)
+            .filter(|picture| picture.width() > 0 && picture.height() > 0)
+            .ok_or_else(|| format!("{} has no pixels", source_path.display()))?;
+        Ok(fwi::FwiImage::from_picture(&::image::DynamicImage::ImageRgba8(picture)))
+    } else {
+        let picture = ::image::open(source_path)
+            .map_err(|e| format!("Could not open {}: {}", source_path.display(), e))?;
+        Ok(fwi::FwiImage::from_picture(&picture))
+    }
+}
+
+fn prune_adjusted(dir: &Path) {
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    files.sort_by_key(|(modified, _)| Reverse(*modified));
+    for (_, path) in files.into_iter().skip(MAX_ADJUSTED) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every_value() -> Vec<u8> {
+        (0..=255u8)
+            .flat_map(|value| [value, 255 - value, value / 2, value])
+            .collect()
+    }
+
+    #[test]
+    fn default_adjustments_leave_pixels_alone() {
+        let mut rgba = every_value();
+        Adjustments::default().apply(&mut rgba);
+        assert_eq!(rgba, every_value());
+    }
+
+    #[test]
+    fn badge_display_darkens_mid_grey() {
+        let mut rgba = vec![128, 128, 128, 200];
+        Adjustments::BADGE_DISPLAY.apply(&mut rgba);
+        assert!(rgba[..3].iter().all(|&channel| channel < 128), "{:?}", rgba);
+        assert_eq!(rgba[0], rgba[1]);
+        assert_eq!(rgba[1], rgba[2]);
+        assert_eq!(rgba[3], 200);
+    }
+}
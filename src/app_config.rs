@@ -0,0 +1,248 @@
+// app_config.rs
+//
+// Settings for the wizard itself, as opposed to the badge: where fwi-serial
+// lives, how long to wait for it, what the wizard starts with and where files go
+// on the badge. Read once from `config.toml` in the platform config folder;
+// `BUILD_A_BADGE_*` environment variables win over the file.
+
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::LedMode;
+
+const ENV_PREFIX: &str = "BUILD_A_BADGE_";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// The fwi-serial executable; a bare name is looked up on PATH.
+    pub fwi_serial: PathBuf,
+    /// LED mode picked on start, by name ("Rainbow Chase") or number (4).
+    pub default_led_mode: String,
+    /// Gallery picture picked on start ("defcon_logo", "doge", ...). Empty for none.
+    pub default_image: String,
+    /// Event theme pack: a folder name in the themes folder, or a path. Empty for
+    /// the built-in look.
+    pub theme_pack: String,
+    /// Folder of extra pictures for the Customize Badge screen. Empty for the
+    /// `images` folder next to the saved designs.
+    pub image_library: String,
+    /// WASM apps larger than this get a warning before they are uploaded.
+    pub max_module_kb: u64,
+    /// Initial linear memory, in 64 KiB pages, past which an app gets a warning.
+    pub max_memory_pages: u64,
+    pub timeouts: Timeouts,
+    pub remote_paths: RemotePaths,
+    /// Problems found while loading, shown on the Welcome screen.
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+/// How long each fwi-serial call may take, in seconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timeouts {
+    /// Everything without its own entry: backups, reading the badge, the file browser.
+    pub command: u64,
+    pub config_upload: u64,
+    pub image_upload: u64,
+    pub wasm_upload: u64,
+    pub settings_upload: u64,
+    pub run_app: u64,
+}
+
+/// Where the wizard's files go on the badge. The bundled app reads the defaults,
+/// so only change these for firmware or apps that look elsewhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemotePaths {
+    pub config: String,
+    pub settings: String,
+    pub image: String,
+    pub scripts_dir: String,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            fwi_serial: PathBuf::from("fwi-serial"),
+            default_led_mode: LedMode::Accel.display_name().to_string(),
+            default_image: String::new(),
+            theme_pack: String::new(),
+            image_library: String::new(),
+            max_module_kb: 256,
+            max_memory_pages: 4,
+            timeouts: Timeouts::default(),
+            remote_paths: RemotePaths::default(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            command: 30,
+            config_upload: 30,
+            image_upload: 60,
+            wasm_upload: 60,
+            settings_upload: 30,
+            run_app: 30,
+        }
+    }
+}
+
+impl Default for RemotePaths {
+    fn default() -> Self {
+        Self {
+            config: "/build_a_badge.txt".to_string(),
+            settings: "/settings.txt".to_string(),
+            image: "/images/build_a_badge.fwi".to_string(),
+            scripts_dir: "/scripts".to_string(),
+        }
+    }
+}
+
+impl Timeouts {
+    pub fn command(&self) -> Duration {
+        Duration::from_secs(self.command)
+    }
+}
+
+impl RemotePaths {
+    /// Where an app called `file_name` is stored on the badge.
+    pub fn script(&self, file_name: &str) -> String {
+        format!("{}/{}", self.scripts_dir.trim_end_matches('/'), file_name)
+    }
+}
+
+static APP_CONFIG: LazyLock<AppConfig> = LazyLock::new(AppConfig::load);
+
+/// The configuration for this run of the wizard.
+pub fn get() -> &'static AppConfig {
+    &APP_CONFIG
+}
+
+/// `BUILD_A_BADGE_CONFIG`, or `config.toml` in the platform config folder.
+pub fn config_path() -> PathBuf {
+    std::env::var_os(format!("{}CONFIG", ENV_PREFIX))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            dirs::config_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join("build_a_badge")
+                .join("config.toml")
+        })
+}
+
+impl AppConfig {
+    fn load() -> Self {
+        let path = config_path();
+        let mut config = match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| AppConfig {
+                warnings: vec![format!("Ignoring {}: {}", path.display(), e)],
+                ..AppConfig::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => AppConfig::default(),
+            Err(e) => AppConfig {
+                warnings: vec![format!("Could not read {}: {}", path.display(), e)],
+                ..AppConfig::default()
+            },
+        };
+
+        config.apply_env();
+        if config.led_mode().is_none() {
+            config.warnings.push(format!(
+                "Unknown default LED mode \"{}\"",
+                config.default_led_mode
+            ));
+        }
+        for warning in &config.warnings {
+            println!("App config: {}", warning);
+        }
+        config
+    }
+
+    /// Leaves a copy of the defaults at `config_path()` so there's something to
+    /// edit. Does nothing if a config file is already there.
+    pub fn write_defaults() {
+        let path = config_path();
+        if path.exists() {
+            return;
+        }
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        match toml::to_string_pretty(&AppConfig::default()) {
+            Ok(contents) => {
+                if let Err(e) = std::fs::write(&path, contents) {
+                    println!("App config: Could not write {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => println!("App config: Could not serialise the defaults: {}", e),
+        }
+    }
+
+    fn apply_env(&mut self) {
+        let var = |name: &str| std::env::var(format!("{}{}", ENV_PREFIX, name)).ok();
+
+        if let Some(value) = var("FWI_SERIAL") {
+            self.fwi_serial = PathBuf::from(value);
+        }
+        if let Some(value) = var("DEFAULT_LED_MODE") {
+            self.default_led_mode = value;
+        }
+        if let Some(value) = var("DEFAULT_IMAGE") {
+            self.default_image = value;
+        }
+        if let Some(value) = var("THEME_PACK") {
+            self.theme_pack = value;
+        }
+        if let Some(value) = var("IMAGE_LIBRARY") {
+            self.image_library = value;
+        }
+
+        let timeouts = [
+            ("TIMEOUT_COMMAND", &mut self.timeouts.command),
+            ("TIMEOUT_CONFIG_UPLOAD", &mut self.timeouts.config_upload),
+            ("TIMEOUT_IMAGE_UPLOAD", &mut self.timeouts.image_upload),
+            ("TIMEOUT_WASM_UPLOAD", &mut self.timeouts.wasm_upload),
+            (
+                "TIMEOUT_SETTINGS_UPLOAD",
+                &mut self.timeouts.settings_upload,
+            ),
+            ("TIMEOUT_RUN_APP", &mut self.timeouts.run_app),
+        ];
+        for (name, seconds) in timeouts {
+            if let Some(value) = var(name) {
+                match value.trim().parse() {
+                    Ok(value) => *seconds = value,
+                    Err(_) => self.warnings.push(format!(
+                        "{}{} must be a number of seconds, not \"{}\"",
+                        ENV_PREFIX, name, value
+                    )),
+                }
+            }
+        }
+
+        let remote_paths = [
+            ("REMOTE_CONFIG", &mut self.remote_paths.config),
+            ("REMOTE_SETTINGS", &mut self.remote_paths.settings),
+            ("REMOTE_IMAGE", &mut self.remote_paths.image),
+            ("REMOTE_SCRIPTS_DIR", &mut self.remote_paths.scripts_dir),
+        ];
+        for (name, path) in remote_paths {
+            if let Some(value) = var(name) {
+                *path = value;
+            }
+        }
+    }
+
+    /// `default_led_mode` as an LED mode, matching names without case or spaces.
+    pub fn led_mode(&self) -> Option<LedMode> {
+        LedMode::from_name(&self.default_led_mode)
+    }
+}
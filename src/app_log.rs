@@ -0,0 +1,87 @@
+// app_log.rs
+//
+// Splits the badge's serial output into timestamped, levelled lines for the
+// "Badge Output" panel that follows a successful configuration run.
+
+use std::time::Instant;
+
+/// Lines kept for the panel; the oldest are dropped first.
+pub const MAX_LINES: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    // printf-style badge scripts don't have real levels; go by the usual words
+    fn detect(line: &str) -> Self {
+        let lower = line.to_ascii_lowercase();
+        if ["error", "fail", "panic", "trap"]
+            .iter()
+            .any(|word| lower.contains(word))
+        {
+            LogLevel::Error
+        } else if lower.contains("warn") {
+            LogLevel::Warn
+        } else if lower.contains("debug") || lower.starts_with("dbg") {
+            LogLevel::Debug
+        } else {
+            LogLevel::Info
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    /// Seconds since streaming started, which lines up with the badge's boot.
+    pub elapsed_secs: f32,
+    pub level: LogLevel,
+    pub text: String,
+}
+
+impl LogLine {
+    pub fn timestamp(&self) -> String {
+        format!("+{:>8.3}s", self.elapsed_secs)
+    }
+}
+
+/// Turns a byte stream into complete lines, holding back any trailing partial line.
+#[derive(Debug)]
+pub struct LogParser {
+    started: Instant,
+    partial: String,
+}
+
+impl LogParser {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            partial: String::new(),
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<LogLine> {
+        self.partial.push_str(&String::from_utf8_lossy(bytes));
+
+        let Some(last_newline) = self.partial.rfind('\n') else {
+            return Vec::new();
+        };
+        let complete: String = self.partial.drain(..=last_newline).collect();
+        let elapsed_secs = self.started.elapsed().as_secs_f32();
+
+        complete
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| LogLine {
+                elapsed_secs,
+                level: LogLevel::detect(line),
+                text: line.to_string(),
+            })
+            .collect()
+    }
+}
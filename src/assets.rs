@@ -0,0 +1,170 @@
+// assets.rs
+//
+// The wizard's own artwork: logo, step icons and the empty badge picture. The
+// source PNGs are nearly 2000 pixels across but drawn at a few hundred, and
+// having iced decode them at full size on first draw made the first screens
+// stall on slow laptops. Instead they're decoded once, in the background, and
+// scaled down to what the screens need; until then `handle` returns a plain
+// placeholder. Theme pack pictures (logos and the gallery) get the same
+// treatment through `picture`, keyed by their path.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+use iced::widget::image;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Asset {
+    Logo,
+    /// The badge outline shown until a picture is picked.
+    BadgePlaceholder,
+    StuffMeIcon,
+    LightMeIcon,
+    PlaceMeIcon,
+    PlugMeIcon,
+}
+
+impl Asset {
+    const ALL: [Asset; 6] = [
+        Asset::Logo,
+        Asset::BadgePlaceholder,
+        Asset::StuffMeIcon,
+        Asset::LightMeIcon,
+        Asset::PlaceMeIcon,
+        Asset::PlugMeIcon,
+    ];
+
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Asset::Logo => include_bytes!("../assets/logo.png"),
+            Asset::BadgePlaceholder => include_bytes!("../assets/badge_placeholder.png"),
+            Asset::StuffMeIcon => include_bytes!("../assets/stuff_me_icon.png"),
+            Asset::LightMeIcon => include_bytes!("../assets/light_me_icon.png"),
+            Asset::PlaceMeIcon => include_bytes!("../assets/place_me_icon.png"),
+            Asset::PlugMeIcon => include_bytes!("../assets/plug_me_icon.png"),
+        }
+    }
+
+    /// Decoded size: twice the largest size a screen draws it at, so it stays
+    /// sharp on high-DPI displays.
+    fn decoded_size(self) -> (u32, u32) {
+        match self {
+            Asset::Logo => (600, 500),
+            Asset::BadgePlaceholder => (800, 800),
+            Asset::StuffMeIcon | Asset::LightMeIcon | Asset::PlaceMeIcon | Asset::PlugMeIcon => {
+                (240, 240)
+            }
+        }
+    }
+}
+
+/// Decoded size of theme pack pictures, which are drawn at up to the badge
+/// screen's size scaled up for the Customize Badge preview.
+const PICTURE_SIZE: (u32, u32) = (960, 720);
+
+static DECODED: LazyLock<Mutex<HashMap<Asset, image::Handle>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static PICTURES: LazyLock<Mutex<HashMap<PathBuf, image::Handle>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Light grey, drawn stretched wherever an asset isn't ready yet.
+static PLACEHOLDER: LazyLock<image::Handle> =
+    LazyLock::new(|| image::Handle::from_pixels(1, 1, vec![235, 235, 235, 255]));
+
+/// `asset` if it has been decoded, otherwise the placeholder.
+pub fn handle(asset: Asset) -> image::Handle {
+    DECODED
+        .lock()
+        .ok()
+        .and_then(|decoded| decoded.get(&asset).cloned())
+        .unwrap_or_else(|| PLACEHOLDER.clone())
+}
+
+/// Decodes every asset, each on its own blocking thread. The wizard redraws with
+/// the real artwork once this finishes.
+pub async fn decode_all() {
+    let started = Instant::now();
+    let tasks = Asset::ALL.map(|asset| tokio::task::spawn_blocking(move || decode(asset)));
+    for task in futures::future::join_all(tasks).await {
+        match task {
+            Ok(Ok((asset, handle))) => {
+                if let Ok(mut decoded) = DECODED.lock() {
+                    decoded.insert(asset, handle);
+                }
+            }
+            Ok(Err(error)) => println!("Assets: {}", error),
+            Err(e) => println!("Assets: Decoding stopped: {}", e),
+        }
+    }
+    println!("Assets: Decoded in {} ms", started.elapsed().as_millis());
+}
+
+/// The picture at `path` if `decode_pictures` has decoded it, otherwise the placeholder.
+pub fn picture(path: &Path) -> image::Handle {
+    PICTURES
+        .lock()
+        .ok()
+        .and_then(|pictures| pictures.get(path).cloned())
+        .unwrap_or_else(|| PLACEHOLDER.clone())
+}
+
+/// Decodes the pictures at `paths` not decoded yet, each on its own blocking
+/// thread. Ones that fail stay as the placeholder.
+pub async fn decode_pictures(paths: Vec<PathBuf>) {
+    let started = Instant::now();
+    let paths: Vec<PathBuf> = match PICTURES.lock() {
+        Ok(pictures) => paths
+            .into_iter()
+            .filter(|path| !pictures.contains_key(path))
+            .collect(),
+        Err(_) => return,
+    };
+    let count = paths.len();
+    let tasks = paths
+        .into_iter()
+        .map(|path| tokio::task::spawn_blocking(move || decode_picture(path)));
+    for task in futures::future::join_all(tasks).await {
+        match task {
+            Ok(Ok((path, handle))) => {
+                if let Ok(mut pictures) = PICTURES.lock() {
+                    pictures.insert(path, handle);
+                }
+            }
+            Ok(Err(error)) => println!("Assets: {}", error),
+            Err(e) => println!("Assets: Decoding stopped: {}", e),
+        }
+    }
+    if count > 0 {
+        println!(
+            "Assets: Decoded {} picture(s) in {} ms",
+            count,
+            started.elapsed().as_millis()
+        );
+    }
+}
+
+fn decode_picture(path: PathBuf) -> Result<(PathBuf, image::Handle), String> {
+    let picture =
+        ::image::open(&path).map_err(|e| format!("Could not decode {}: {}", path.display(), e))?;
+    let (width, height) = PICTURE_SIZE;
+    // Gallery pictures are usually badge-sized already; only big ones are worth scaling
+    let scaled = if picture.width() > width || picture.height() > height {
+        picture.thumbnail(width, height).to_rgba8()
+    } else {
+        picture.to_rgba8()
+    };
+    let handle = image::Handle::from_pixels(scaled.width(), scaled.height(), scaled.into_raw());
+    Ok((path, handle))
+}
+
+fn decode(asset: Asset) -> Result<(Asset, image::Handle), String> {
+    let picture = ::image::load_from_memory(asset.bytes())
+        .map_err(|e| format!("Could not decode {:?}: {}", asset, e))?;
+    let (width, height) = asset.decoded_size();
+    let scaled = picture.thumbnail(width, height).to_rgba8();
+    let handle = image::Handle::from_pixels(scaled.width(), scaled.height(), scaled.into_raw());
+    Ok((asset, handle))
+}
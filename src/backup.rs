@@ -0,0 +1,233 @@
+// backup.rs
+//
+// Tarball backups of the files the wizard overwrites on a badge, so a bad
+// configuration can be rolled back. Archive entries are named after their path
+// on the badge, minus the leading slash.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config_error::ConfigError;
+use crate::{app_config, device};
+
+/// Automatic pre-flash backups kept before the oldest are deleted.
+const MAX_PREFLASH_BACKUPS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupKind {
+    Manual,
+    PreFlash,
+}
+
+impl BackupKind {
+    fn file_prefix(&self) -> &'static str {
+        match self {
+            BackupKind::Manual => "badge",
+            BackupKind::PreFlash => "preflash",
+        }
+    }
+}
+
+pub fn backups_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("build_a_badge")
+        .join("backups")
+}
+
+/// Downloads every wizard file from the badge, plus the app about to be
+/// uploaded at `app_remote_path`, and archives the ones that exist.
+pub async fn backup_badge(kind: BackupKind, app_remote_path: String) -> Result<PathBuf, String> {
+    let staging_dir = std::env::temp_dir().join("build_a_badge_backup");
+    fs::create_dir_all(&staging_dir)
+        .map_err(|e| format!("Could not create {}: {}", staging_dir.display(), e))?;
+
+    let mut remote_paths = device::wizard_files();
+    if !remote_paths.contains(&app_remote_path) {
+        remote_paths.push(app_remote_path);
+    }
+
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    for remote_path in remote_paths {
+        let local_path = staging_dir.join(archive_name(&remote_path).replace('/', "_"));
+        let _ = fs::remove_file(&local_path);
+        match device::download_file(
+            &remote_path,
+            &local_path,
+            app_config::get().timeouts.command(),
+        )
+        .await
+        {
+            Ok(()) => files.push((remote_path, local_path)),
+            // The other files would only wait out the same timeout
+            Err(error) if is_unreachable(&error) => return Err(error),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    if files.is_empty() {
+        return Err(format!("Nothing to back up:\n{}", errors.join("\n")));
+    }
+
+    let dir = backups_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    let archive_path = dir.join(format!("{}-{}.tar", kind.file_prefix(), timestamp()));
+
+    let write_archive = || -> std::io::Result<()> {
+        let mut builder = tar::Builder::new(fs::File::create(&archive_path)?);
+        for (remote_path, local_path) in &files {
+            builder.append_path_with_name(local_path, archive_name(remote_path))?;
+        }
+        builder.finish()
+    };
+    write_archive().map_err(|e| format!("Could not write {}: {}", archive_path.display(), e))?;
+
+    println!(
+        "Backup: Saved {} file(s) to {}",
+        files.len(),
+        archive_path.display()
+    );
+
+    if kind == BackupKind::PreFlash {
+        prune_preflash_backups(&dir);
+    }
+
+    Ok(archive_path)
+}
+
+/// Uploads every file in `archive_path` back to its original location.
+pub async fn restore_backup(archive_path: &Path) -> Result<String, String> {
+    let staging_dir = std::env::temp_dir().join("build_a_badge_restore");
+    fs::create_dir_all(&staging_dir)
+        .map_err(|e| format!("Could not create {}: {}", staging_dir.display(), e))?;
+
+    let mut entries = Vec::new();
+    let read_archive = |entries: &mut Vec<(String, PathBuf)>| -> std::io::Result<()> {
+        let mut archive = tar::Archive::new(fs::File::open(archive_path)?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().into_owned();
+            let remote_path = format!("/{}", name.trim_start_matches('/'));
+            // Only restore files the wizard manages; an archive must not write anywhere else
+            if !device::is_wizard_file(&remote_path) {
+                println!("Restore: Skipping unexpected entry {}", name);
+                continue;
+            }
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            let local_path = staging_dir.join(name.replace('/', "_"));
+            fs::write(&local_path, contents)?;
+            entries.push((remote_path, local_path));
+        }
+        Ok(())
+    };
+    read_archive(&mut entries)
+        .map_err(|e| format!("Could not read {}: {}", archive_path.display(), e))?;
+
+    if entries.is_empty() {
+        return Err(format!("{} contains no badge files", archive_path.display()));
+    }
+
+    for (remote_path, local_path) in &entries {
+        device::upload_file(
+            local_path,
+            remote_path,
+            app_config::get().timeouts.command(),
+        )
+        .await?;
+    }
+
+    Ok(format!(
+        "Restored {} file(s) from {}",
+        entries.len(),
+        archive_path.display()
+    ))
+}
+
+/// Whether `error` means no badge will answer, rather than that one file is missing.
+fn is_unreachable(error: &str) -> bool {
+    matches!(
+        ConfigError::from_device(error),
+        ConfigError::Timeout(_) | ConfigError::DeviceNotConnected(_) | ConfigError::ToolNotFound(_)
+    )
+}
+
+fn archive_name(remote_path: &str) -> &str {
+    remote_path.trim_start_matches('/')
+}
+
+fn prune_preflash_backups(dir: &Path) {
+    let prefix = format!("{}-", BackupKind::PreFlash.file_prefix());
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+
+    // Timestamps sort lexically, so the oldest come first
+    let mut backups: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix))
+        })
+        .collect();
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(MAX_PREFLASH_BACKUPS);
+    for old_backup in &backups[..excess] {
+        let _ = fs::remove_file(old_backup);
+    }
+}
+
+/// UTC `YYYYMMDD-HHMMSS-mmm`, without pulling in a date crate. Milliseconds keep
+/// two backups taken in the same second from overwriting each other.
+fn timestamp() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0);
+    format_timestamp(millis)
+}
+
+fn format_timestamp(millis: u64) -> String {
+    let seconds = millis / 1_000;
+    let (days, time_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Howard Hinnant's days-to-civil algorithm
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}",
+        year,
+        month,
+        day,
+        time_of_day / 3_600,
+        time_of_day % 3_600 / 60,
+        time_of_day % 60,
+        millis % 1_000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_civil_dates() {
+        assert_eq!(format_timestamp(0), "19700101-000000-000");
+        // Leap day, in a century year that is still a leap year
+        assert_eq!(format_timestamp(951_782_400_000), "20000229-000000-000");
+        assert_eq!(format_timestamp(1_792_413_045_678), "20261019-123045-678");
+    }
+}
@@ -0,0 +1,72 @@
+// badge_config.rs
+//
+// Model of `/build_a_badge.txt`, the file the badge script reads at boot:
+//
+//   <name>-WiLi
+//   <led mode integer>
+
+use crate::LedMode;
+use crate::validation::NAME_SUFFIX;
+
+/// Name written when the user leaves the NameBadge screen empty.
+const FALLBACK_NAME: &str = "Boring";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadgeConfig {
+    pub name: String,
+    pub led_mode: Option<LedMode>,
+}
+
+impl BadgeConfig {
+    pub fn new(name: &str, led_mode: Option<LedMode>) -> Self {
+        Self {
+            name: name.to_string(),
+            led_mode,
+        }
+    }
+
+    /// Parses the file as read back from a badge. The `-WiLi` suffix is stripped
+    /// so the name can be put straight back into the NameBadge screen.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut lines = contents.lines().map(str::trim);
+
+        let full_name = lines
+            .next()
+            .filter(|line| !line.is_empty())
+            .ok_or_else(|| "build_a_badge.txt is empty".to_string())?;
+        let name = full_name.strip_suffix(NAME_SUFFIX).unwrap_or(full_name);
+
+        let led_mode = match lines.next().filter(|line| !line.is_empty()) {
+            Some(line) => {
+                let value: u8 = line
+                    .parse()
+                    .map_err(|_| format!("Invalid LED mode in build_a_badge.txt: {line}"))?;
+                Some(
+                    LedMode::from_integer(value)
+                        .ok_or_else(|| format!("Unknown LED mode in build_a_badge.txt: {value}"))?,
+                )
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            led_mode,
+        })
+    }
+
+    pub fn to_file_contents(&self) -> String {
+        let led_pattern = match self.led_mode {
+            Some(mode) => mode.as_integer().to_string(),
+            None => "0".to_string(), // Default to Manual (0)
+        };
+
+        let name = if self.name.is_empty() {
+            FALLBACK_NAME
+        } else {
+            &self.name
+        };
+
+        format!("{name}{NAME_SUFFIX}\n{led_pattern}\n")
+    }
+}
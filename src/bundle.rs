@@ -0,0 +1,393 @@
+// bundle.rs
+//
+// `.badge` files: a whole design in one zip, so it can be mailed to whoever has
+// the hardware. Entries:
+//
+//   manifest.toml   see `Manifest`
+//   image.fwi       the picture, exactly as it is uploaded
+//   picture.fwi     the picture before adjustments and layers, if it has any
+//   source.*        the picture the .fwi was made from, if known
+//   <app>.wasm      the badge app, unless it's the one bundled with the wizard
+//
+// Importing unpacks a bundle as a saved design (see `design.rs`), so it shows up
+// in the recent designs list like anything made on this computer. `apply` on the
+// command line only needs the files, so it unpacks into a scratch folder instead.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+
+use crate::adjust::Adjustments;
+use crate::design::{self, Design, SavedDesign};
+use crate::image_library;
+use crate::layers::Layer;
+use crate::wasm_apps::{self, BUNDLED_APP_FILE, WasmApp};
+
+pub const EXTENSION: &str = "badge";
+
+/// Bumped when a bundle can no longer be read by older versions of the wizard.
+const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.toml";
+const IMAGE_FILE: &str = "image.fwi";
+const PICTURE_FILE: &str = "picture.fwi";
+
+/// Entries larger than this are refused on import rather than unpacked.
+const MAX_ENTRY_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub created_by: String,
+    pub badge_name: String,
+    /// The badge's LED mode number, as written to `build_a_badge.txt`.
+    pub led_mode: Option<u8>,
+    /// Gallery picture by asset name, so the importer can highlight it.
+    pub gallery_image: Option<String>,
+    /// Entry holding the original picture.
+    pub source_image: Option<String>,
+    /// Entry holding the badge app; `None` for the app bundled with the wizard.
+    pub wasm_app: Option<String>,
+    pub settings_txt: String,
+    #[serde(default)]
+    pub adjustments: Adjustments,
+    /// Frames and stickers by name, which only show if the importer has the
+    /// same theme pack; `image.fwi` has them either way.
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+}
+
+/// Writes `design` to `path`. `picture_fwi` is the picture under the design's
+/// adjustments and layers, and `source_image` the picture it was made from.
+pub fn export(
+    path: &Path,
+    design: &Design,
+    image_fwi: &Path,
+    picture_fwi: Option<&Path>,
+    source_image: Option<&Path>,
+    wasm_app: &WasmApp,
+) -> Result<(), String> {
+    let read = |path: &Path| {
+        fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))
+    };
+
+    let mut entries = vec![(IMAGE_FILE.to_string(), read(image_fwi)?)];
+    if let Some(picture_fwi) = picture_fwi {
+        entries.push((PICTURE_FILE.to_string(), read(picture_fwi)?));
+    }
+    let source_entry = match source_image {
+        Some(source) => {
+            let extension = source
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_lowercase())
+                .unwrap_or_else(|| "png".to_string());
+            let name = format!("source.{}", extension);
+            entries.push((name.clone(), read(source)?));
+            Some(name)
+        }
+        None => None,
+    };
+    let wasm_entry = if wasm_app.is_bundled {
+        None
+    } else {
+        let name = wasm_app.file_name();
+        entries.push((name.clone(), read(&wasm_app.local_path)?));
+        Some(name)
+    };
+
+    let manifest = Manifest {
+        format: FORMAT_VERSION,
+        created_by: format!("build_a_badge {}", env!("CARGO_PKG_VERSION")),
+        badge_name: design.badge_name.clone(),
+        led_mode: design.led_mode,
+        gallery_image: design.gallery_image.clone(),
+        source_image: source_entry,
+        wasm_app: wasm_entry,
+        settings_txt: design.settings_txt.clone(),
+        adjustments: design.adjustments,
+        layers: design.layers.clone(),
+    };
+    let manifest = toml::to_string_pretty(&manifest)
+        .map_err(|e| format!("Could not write the bundle manifest: {}", e))?;
+    entries.insert(0, (MANIFEST_FILE.to_string(), manifest.into_bytes()));
+
+    let write_zip = || -> zip::result::ZipResult<()> {
+        let mut zip = zip::ZipWriter::new(fs::File::create(path)?);
+        for (name, contents) in &entries {
+            zip.start_file(name.as_str(), SimpleFileOptions::default())?;
+            zip.write_all(contents)?;
+        }
+        zip.finish()?;
+        Ok(())
+    };
+    write_zip().map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+
+    println!("Bundle: Exported {}", path.display());
+    Ok(())
+}
+
+/// Unpacks the bundle at `path` as a new saved design.
+pub fn import(path: &Path) -> Result<SavedDesign, String> {
+    let id = design::new_id();
+    let dir = design::designs_dir().join(&id);
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    let result = unpack_bundle(path, &dir).and_then(|(design, image_path, picture_path)| {
+        design::save(&id, &design, &image_path, picture_path.as_deref(), false)
+    });
+    if let Err(error) = result {
+        // Half an import would otherwise linger in the recent designs folder
+        let _ = fs::remove_dir_all(&dir);
+        return Err(error);
+    }
+
+    println!("Bundle: Imported {} as {}", path.display(), id);
+    design::load(&id).ok_or_else(|| format!("Could not reopen design {}", id))
+}
+
+/// Unpacks the bundle at `path` into `dir`, replacing whatever was there, and
+/// returns its design and `image.fwi`. Nothing is added to the recent designs.
+pub fn unpack(path: &Path, dir: &Path) -> Result<(Design, PathBuf), String> {
+    let _ = fs::remove_dir_all(dir);
+    fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    let (design, image_path, _) = unpack_bundle(path, dir)?;
+    println!("Bundle: Unpacked {} into {}", path.display(), dir.display());
+    Ok((design, image_path))
+}
+
+fn unpack_bundle(path: &Path, dir: &Path) -> Result<(Design, PathBuf, Option<PathBuf>), String> {
+    let file =
+        fs::File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    let mut zip = zip::ZipArchive::new(file)
+        .map_err(|e| format!("{} is not a .badge file: {}", path.display(), e))?;
+
+    let mut read_entry = |name: &str| -> Result<Vec<u8>, String> {
+        let entry = zip
+            .by_name(name)
+            .map_err(|e| format!("{} has no {}: {}", path.display(), name, e))?;
+        if entry.size() > MAX_ENTRY_BYTES {
+            return Err(format!("{} in {} is too large", name, path.display()));
+        }
+        let mut contents = Vec::new();
+        entry
+            .take(MAX_ENTRY_BYTES)
+            .read_to_end(&mut contents)
+            .map_err(|e| format!("Could not read {} from {}: {}", name, path.display(), e))?;
+        Ok(contents)
+    };
+
+    let manifest: Manifest = toml::from_str(&String::from_utf8_lossy(&read_entry(MANIFEST_FILE)?))
+        .map_err(|e| format!("{} has a broken manifest: {}", path.display(), e))?;
+    if manifest.format > FORMAT_VERSION {
+        return Err(format!(
+            "{} was made by a newer version of the wizard; update to open it",
+            path.display()
+        ));
+    }
+
+    unpack_design(dir, manifest, &mut read_entry)
+}
+
+/// The file name the manifest gives an entry, unless it could land on top of
+/// one of the bundle's own files.
+fn manifest_entry_name(name: &str) -> Result<&str, String> {
+    // Entry names come from someone else's computer; only the file name is trusted
+    let file_name = Path::new(name)
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .ok_or_else(|| format!("Bad entry name {} in the bundle", name))?;
+    if [MANIFEST_FILE, IMAGE_FILE, PICTURE_FILE]
+        .iter()
+        .any(|reserved| file_name.eq_ignore_ascii_case(reserved))
+    {
+        return Err(format!("The bundle can't name an entry {}", file_name));
+    }
+    Ok(file_name)
+}
+
+/// Writes the bundle's files into `dir` and returns the design they make up,
+/// with its `image.fwi` and `picture.fwi`.
+fn unpack_design(
+    dir: &Path,
+    manifest: Manifest,
+    read_entry: &mut impl FnMut(&str) -> Result<Vec<u8>, String>,
+) -> Result<(Design, PathBuf, Option<PathBuf>), String> {
+    let unpack = |file_name: &str, contents: &[u8]| {
+        let target = dir.join(file_name);
+        fs::write(&target, contents)
+            .map(|()| target)
+            .map_err(|e| format!("Could not write {}: {}", dir.display(), e))
+    };
+
+    let image_path = unpack(IMAGE_FILE, &read_entry(IMAGE_FILE)?)?;
+    let is_edited = !manifest.adjustments.is_identity() || !manifest.layers.is_empty();
+    let picture_path = if is_edited {
+        // Without it the design reopens as image.fwi alone (see `SavedDesign::picture_path`)
+        read_entry(PICTURE_FILE)
+            .ok()
+            .map(|contents| unpack(PICTURE_FILE, &contents))
+            .transpose()?
+    } else {
+        None
+    };
+    // Unpacked under a name of our own, so only the extension is the sender's
+    let source_image = match &manifest.source_image {
+        Some(name) => {
+            let file_name = manifest_entry_name(name)?;
+            if !image_library::is_picture(Path::new(file_name)) {
+                return Err(format!("{} in the bundle is not a picture", file_name));
+            }
+            let extension = Path::new(file_name)
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            Some(unpack(
+                &format!("source.{}", extension),
+                &read_entry(name)?,
+            )?)
+        }
+        None => None,
+    };
+    let wasm_app = match &manifest.wasm_app {
+        Some(name) => {
+            let file_name = manifest_entry_name(name)?;
+            if !wasm_apps::is_wasm_file_name(file_name) {
+                return Err(format!("{} in the bundle is not a .wasm file", file_name));
+            }
+            let wasm_path = unpack(file_name, &read_entry(name)?)?;
+            // Same checks as adding an app by hand
+            WasmApp::from_file(&wasm_path)?.local_path
+        }
+        None => WasmApp::bundled()
+            .into_iter()
+            .find(|app| app.file_name() == BUNDLED_APP_FILE)
+            .map(|app| app.local_path)
+            .unwrap_or_default(),
+    };
+
+    let design = Design {
+        badge_name: manifest.badge_name,
+        led_mode: manifest.led_mode,
+        gallery_image: manifest.gallery_image,
+        source_image,
+        wasm_app,
+        settings_txt: manifest.settings_txt,
+        adjustments: manifest.adjustments,
+        layers: manifest.layers,
+    };
+    Ok((design, image_path, picture_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::LayerKind;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join("build_a_badge_bundle_tests")
+            .join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn edited_design() -> Design {
+        Design {
+            badge_name: "Ada".to_string(),
+            led_mode: Some(4),
+            gallery_image: None,
+            source_image: None,
+            wasm_app: PathBuf::from(BUNDLED_APP_FILE),
+            settings_txt: "wifiAPEn=0\n".to_string(),
+            adjustments: Adjustments {
+                brightness: 0.25,
+                ..Adjustments::default()
+            },
+            layers: vec![Layer {
+                kind: LayerKind::Sticker,
+                name: "star".to_string(),
+                x: 100.0,
+                y: 80.0,
+                scale: 1.5,
+                rotation: 45.0,
+            }],
+        }
+    }
+
+    #[test]
+    fn export_then_unpack_round_trips() {
+        let dir = scratch_dir("round_trip");
+        let bundle_path = dir.join("ada.badge");
+        let design = edited_design();
+        export(
+            &bundle_path,
+            &design,
+            Path::new("assets/doge.fwi"),
+            Some(Path::new("assets/puppy.fwi")),
+            Some(Path::new("assets/doge.png")),
+            &WasmApp::bundled()[0],
+        )
+        .unwrap();
+
+        let unpacked_dir = dir.join("unpacked");
+        let (unpacked, image_path) = unpack(&bundle_path, &unpacked_dir).unwrap();
+        let source_path = unpacked_dir.join("source.png");
+        assert_eq!(
+            unpacked,
+            Design {
+                source_image: Some(source_path.clone()),
+                ..design
+            }
+        );
+        assert_eq!(
+            fs::read(image_path).unwrap(),
+            fs::read("assets/doge.fwi").unwrap()
+        );
+        assert_eq!(
+            fs::read(unpacked_dir.join(PICTURE_FILE)).unwrap(),
+            fs::read("assets/puppy.fwi").unwrap()
+        );
+        assert_eq!(
+            fs::read(source_path).unwrap(),
+            fs::read("assets/doge.png").unwrap()
+        );
+    }
+
+    #[test]
+    fn reserved_entry_names_are_refused() {
+        let dir = scratch_dir("reserved");
+        let design = edited_design();
+        let manifest = |source_image: Option<&str>, wasm_app: Option<&str>| Manifest {
+            format: FORMAT_VERSION,
+            created_by: "test".to_string(),
+            badge_name: design.badge_name.clone(),
+            led_mode: design.led_mode,
+            gallery_image: None,
+            source_image: source_image.map(str::to_string),
+            wasm_app: wasm_app.map(str::to_string),
+            settings_txt: design.settings_txt.clone(),
+            adjustments: Adjustments::default(),
+            layers: Vec::new(),
+        };
+        let image = fs::read("assets/doge.fwi").unwrap();
+
+        for (index, manifest) in [
+            manifest(Some(IMAGE_FILE), None),
+            manifest(Some("../picture.fwi"), None),
+            manifest(None, Some(MANIFEST_FILE)),
+            manifest(None, Some("Image.FWI")),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let target = dir.join(index.to_string());
+            fs::create_dir_all(&target).unwrap();
+            // Every name resolves, so only the name checks can refuse the bundle
+            let result = unpack_design(&target, manifest, &mut |_: &str| Ok(image.clone()));
+            assert!(result.is_err(), "{:?}", result);
+        }
+    }
+}
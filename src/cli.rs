@@ -0,0 +1,115 @@
+// cli.rs
+//
+// Command line use of the wizard, for setting up a stack of badges from one
+// `.badge` file without clicking through the screens each time:
+//
+//   build_a_badge apply <design.badge>
+//
+// Runs the same configuration pipeline as the Summary screen and writes the same
+// per-run log. With no arguments the wizard opens as usual.
+
+use std::path::Path;
+
+use crate::LedMode;
+use crate::config_error::ConfigFailure;
+use crate::pipeline::{self, ConfigJob};
+use crate::settings::DeviceSettings;
+use crate::wasm_apps::WasmApp;
+use crate::{bundle, run_log};
+
+const USAGE: &str = "Usage: build_a_badge [apply <design.badge>]";
+
+/// Handles `args` (without the program name) and returns the exit code, or
+/// `None` when the window should open instead.
+pub fn run(args: &[String]) -> Option<i32> {
+    match args {
+        [] => None,
+        [command, path] if command == "apply" => Some(apply(Path::new(path))),
+        [flag] if flag == "-h" || flag == "--help" => {
+            println!("{}", USAGE);
+            Some(0)
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            Some(2)
+        }
+    }
+}
+
+/// Unpacks the bundle at `path` and configures the connected badge with it.
+fn apply(path: &Path) -> i32 {
+    let dir = std::env::temp_dir().join("build_a_badge_apply");
+    let (design, image_path) = match bundle::unpack(path, &dir) {
+        Ok(unpacked) => unpacked,
+        Err(error) => {
+            eprintln!("✗ {}", error);
+            return 1;
+        }
+    };
+
+    let wasm_app = match WasmApp::bundled()
+        .into_iter()
+        .find(|app| app.local_path == design.wasm_app)
+    {
+        Some(app) => app,
+        None => match WasmApp::from_file(&design.wasm_app) {
+            Ok(app) => app,
+            Err(error) => {
+                eprintln!("✗ {}", error);
+                return 1;
+            }
+        },
+    };
+    let job = ConfigJob {
+        image_fwi_path: image_path,
+        led_mode: design.led_mode.and_then(LedMode::from_integer),
+        badge_name: design.badge_name.clone(),
+        device_settings: DeviceSettings::parse(&design.settings_txt, &design.badge_name),
+        wasm_app,
+    };
+
+    match run_log::start_run(&job) {
+        Ok(path) => println!("Logging this run to {}", path.display()),
+        Err(error) => println!("Run log: {}", error),
+    }
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("✗ Could not start the async runtime: {}", e);
+            return 1;
+        }
+    };
+    let result = runtime.block_on(run_steps(&job));
+    run_log::finish_run(&result);
+
+    match result {
+        Ok(message) => {
+            println!("{}", message);
+            0
+        }
+        Err(failure) => {
+            eprintln!("✗ {}", failure);
+            eprintln!("  {}", failure.error.remediation());
+            1
+        }
+    }
+}
+
+async fn run_steps(job: &ConfigJob) -> Result<String, ConfigFailure> {
+    let steps = pipeline::plan(job);
+    for (index, step) in steps.iter().enumerate() {
+        println!("Step {}/{}: {}...", index + 1, steps.len(), step.name);
+        match pipeline::run_step(step).await {
+            Ok(report) => println!("✓ {}", report),
+            // Same as the Summary screen: optional steps never stop the run
+            Err(error) if step.optional => println!("⚠ {} skipped: {}", step.name, error),
+            Err(error) => {
+                return Err(ConfigFailure {
+                    step: step.name,
+                    error,
+                });
+            }
+        }
+    }
+    Ok("Configuration completed successfully!".to_string())
+}
@@ -0,0 +1,201 @@
+// config_error.rs
+//
+// Why a configuration run stopped. fwi-serial reports problems as free text on
+// stderr; `ConfigError::from_device` sorts that text into something the Summary
+// screen can explain, with a fix the user can act on. `ConfigFailure` adds the
+// step it happened in.
+
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// fwi-serial isn't installed, isn't on PATH or can't be started.
+    ToolNotFound(String),
+    /// No badge answered on any serial port.
+    DeviceNotConnected(String),
+    /// The badge stopped answering part-way through.
+    Timeout(String),
+    /// The OS wouldn't let us open the serial port.
+    PermissionDenied(String),
+    /// The badge answered but refused the request, or stored something else.
+    DeviceRejected(String),
+    /// A file on this computer couldn't be written or read.
+    IoWrite(String),
+    /// The selected WASM app can't run on the badge.
+    InvalidApp(String),
+}
+
+impl ConfigError {
+    /// Sorts an error from the `device` module by what fwi-serial printed.
+    pub fn from_device(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let lower = message.to_lowercase();
+        let mentions = |phrases: &[&str]| phrases.iter().any(|phrase| lower.contains(phrase));
+
+        // Both are reported by us, not fwi-serial. A timeout may mention a missing
+        // device, and failing to start the tool says nothing about the serial port.
+        if lower.starts_with("timed out") {
+            ConfigError::Timeout(message)
+        } else if lower.starts_with("could not run fwi-serial") {
+            ConfigError::ToolNotFound(message)
+        } else if mentions(&[
+            "permission denied",
+            "access is denied",
+            "operation not permitted",
+            "errno 13",
+        ]) {
+            ConfigError::PermissionDenied(message)
+        } else if mentions(&[
+            "no freewili",
+            "no free-wili",
+            "no devices found",
+            "no device found",
+            "device not found",
+            "no such device",
+            "could not open port",
+            "could not find",
+            "not connected",
+        ]) {
+            ConfigError::DeviceNotConnected(message)
+        } else {
+            ConfigError::DeviceRejected(message)
+        }
+    }
+
+    pub fn io(path: &Path, error: impl fmt::Display) -> Self {
+        ConfigError::IoWrite(format!("{}: {}", path.display(), error))
+    }
+
+    /// What fwi-serial or the OS actually said.
+    pub fn detail(&self) -> &str {
+        match self {
+            ConfigError::ToolNotFound(detail)
+            | ConfigError::DeviceNotConnected(detail)
+            | ConfigError::Timeout(detail)
+            | ConfigError::PermissionDenied(detail)
+            | ConfigError::DeviceRejected(detail)
+            | ConfigError::IoWrite(detail)
+            | ConfigError::InvalidApp(detail) => detail,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ConfigError::ToolNotFound(_) => "Could not run fwi-serial",
+            ConfigError::DeviceNotConnected(_) => "Badge not connected",
+            ConfigError::Timeout(_) => "Badge stopped responding",
+            ConfigError::PermissionDenied(_) => "No permission to use the serial port",
+            ConfigError::DeviceRejected(_) => "The badge rejected the request",
+            ConfigError::IoWrite(_) => "Could not write a local file",
+            ConfigError::InvalidApp(_) => "The WASM app can't run on the badge",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ConfigError::ToolNotFound(_) => "🧰",
+            ConfigError::DeviceNotConnected(_) => "🔌",
+            ConfigError::Timeout(_) => "⏱",
+            ConfigError::PermissionDenied(_) => "🔒",
+            ConfigError::DeviceRejected(_) => "⛔",
+            ConfigError::IoWrite(_) => "💾",
+            ConfigError::InvalidApp(_) => "🧩",
+        }
+    }
+
+    /// What to try before configuring again.
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            ConfigError::ToolNotFound(_) => {
+                "Install the FreeWili tools (pip install freewili) and make sure fwi-serial is on your PATH and executable, then restart the wizard."
+            }
+            ConfigError::DeviceNotConnected(_) => {
+                "Plug the badge in with a USB data cable and switch it on. Disconnect the serial console or anything else using its port."
+            }
+            ConfigError::Timeout(_) => {
+                "Unplug the badge, plug it back in, wait for it to finish starting up and try again."
+            }
+            ConfigError::PermissionDenied(_) if cfg!(target_os = "linux") => {
+                "Add yourself to the dialout group (sudo usermod -aG dialout $USER), then log out and back in."
+            }
+            ConfigError::PermissionDenied(_) => {
+                "Close other programs using the badge's serial port and try again."
+            }
+            ConfigError::DeviceRejected(_) => {
+                "Make sure the badge has free space and isn't busy running another app, then try again."
+            }
+            ConfigError::IoWrite(_) => {
+                "Check that the wizard's folder is writable and the disk isn't full."
+            }
+            ConfigError::InvalidApp(_) => {
+                "Pick another app on the App Selection screen, or rebuild this one against the badge's host API."
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title(), self.detail())
+    }
+}
+
+/// A `ConfigError` and the configuration step it stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigFailure {
+    /// `ConfigStep::name`.
+    pub step: &'static str,
+    pub error: ConfigError,
+}
+
+impl fmt::Display for ConfigFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed: {}", self.step, self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_device_sorts_fwi_serial_errors() {
+        let cases = [
+            (
+                "Could not run fwi-serial (fwi-serial): No such file or directory (os error 2)",
+                ConfigError::ToolNotFound(String::new()),
+            ),
+            (
+                "Could not run fwi-serial (/usr/local/bin/fwi-serial): Permission denied (os error 13)",
+                ConfigError::ToolNotFound(String::new()),
+            ),
+            (
+                "Timed out trying to write /images/build_a_badge.fwi (30 seconds) - device may not be connected",
+                ConfigError::Timeout(String::new()),
+            ),
+            (
+                "Could not write /settings.txt: could not open port /dev/ttyACM0: [Errno 13] Permission denied: '/dev/ttyACM0'",
+                ConfigError::PermissionDenied(String::new()),
+            ),
+            (
+                "Could not list /scripts: No FreeWili devices found!",
+                ConfigError::DeviceNotConnected(String::new()),
+            ),
+            (
+                "Could not run build_a_badge.wasm: Failed to run script: file error",
+                ConfigError::DeviceRejected(String::new()),
+            ),
+        ];
+        for (message, expected) in cases {
+            let error = ConfigError::from_device(message);
+            assert_eq!(
+                std::mem::discriminant(&error),
+                std::mem::discriminant(&expected),
+                "{}",
+                message
+            );
+            assert_eq!(error.detail(), message);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+// design.rs
+//
+// Badge designs saved to disk as they're edited, so a closed window or a crash
+// doesn't lose a visitor's work. Each design lives in its own folder under
+// `designs_dir()`:
+//
+//   design.toml   what was picked (see `Design`)
+//   image.fwi     the picture, as it would be uploaded
+//   picture.fwi   the picture before adjustments and layers, if it has any
+//   source.*      the original picture, for designs imported from a bundle
+//   <app>.wasm    the badge app, for designs imported from a bundle
+
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use iced::widget::image;
+use serde::{Deserialize, Serialize};
+
+use crate::adjust::Adjustments;
+use crate::fwi;
+use crate::layers::Layer;
+
+/// Designs kept before the least recently edited are deleted.
+const MAX_SAVED_DESIGNS: usize = 24;
+
+const DESIGN_FILE: &str = "design.toml";
+const IMAGE_FILE: &str = "image.fwi";
+const PICTURE_FILE: &str = "picture.fwi";
+
+/// Everything the wizard needs to rebuild a design.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Design {
+    pub badge_name: String,
+    /// The badge's LED mode number, as written to `build_a_badge.txt`.
+    pub led_mode: Option<u8>,
+    /// Gallery picture by asset name; `None` when the picture is `image.fwi` alone.
+    pub gallery_image: Option<String>,
+    /// The picture `image.fwi` was converted from, if it's known, so edits can
+    /// start from the original instead of the quantized `.fwi`.
+    pub source_image: Option<PathBuf>,
+    pub wasm_app: PathBuf,
+    /// The Advanced Settings screen, as it would be written to the badge.
+    pub settings_txt: String,
+    pub adjustments: Adjustments,
+    /// Frames and stickers, bottom first.
+    pub layers: Vec<Layer>,
+}
+
+impl Design {
+    /// Whether `image.fwi` differs from the picture that was picked.
+    pub fn is_edited(&self) -> bool {
+        !self.adjustments.is_identity() || !self.layers.is_empty()
+    }
+}
+
+/// A design on disk, for the Welcome screen.
+#[derive(Debug, Clone)]
+pub struct SavedDesign {
+    pub id: String,
+    pub design: Design,
+    pub saved_at: SystemTime,
+    pub thumbnail: Option<image::Handle>,
+}
+
+impl SavedDesign {
+    pub fn image_path(&self) -> PathBuf {
+        image_path(&self.id)
+    }
+
+    /// The picture before adjustments and layers, if the design has them.
+    pub fn picture_path(&self) -> Option<PathBuf> {
+        let path = designs_dir().join(&self.id).join(PICTURE_FILE);
+        (self.design.is_edited() && path.exists()).then_some(path)
+    }
+
+    /// "5 min ago" and so on, for the recent designs list.
+    pub fn age_text(&self) -> String {
+        let seconds = self
+            .saved_at
+            .elapsed()
+            .map(|age| age.as_secs())
+            .unwrap_or(0);
+        match seconds {
+            0..60 => "just now".to_string(),
+            60..3600 => format!("{} min ago", seconds / 60),
+            3600..86400 => format!("{} h ago", seconds / 3600),
+            _ => format!("{} days ago", seconds / 86400),
+        }
+    }
+}
+
+pub fn designs_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("build_a_badge")
+        .join("designs")
+}
+
+pub fn image_path(id: &str) -> PathBuf {
+    designs_dir().join(id).join(IMAGE_FILE)
+}
+
+/// A folder name for a design started now.
+pub fn new_id() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_millis())
+        .unwrap_or(0);
+    format!("design-{}", now)
+}
+
+/// Writes `design` under `id`. The pictures are copied only when `copy_image` is
+/// set, since they change far less often than the rest. `picture_fwi` is the
+/// picture under the design's adjustments and layers, kept so they can be
+/// changed again after reopening.
+pub fn save(
+    id: &str,
+    design: &Design,
+    image_fwi: &Path,
+    picture_fwi: Option<&Path>,
+    copy_image: bool,
+) -> Result<(), String> {
+    let dir = designs_dir().join(id);
+    let is_new = !dir.exists();
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+
+    let copy = |from: &Path, file: &str| {
+        let to = dir.join(file);
+        // A restored design's picture is already in place; copying a file onto itself empties it
+        if (copy_image || !to.exists()) && from != to {
+            fs::copy(from, &to).map_err(|e| {
+                format!(
+                    "Could not copy {} to {}: {}",
+                    from.display(),
+                    to.display(),
+                    e
+                )
+            })?;
+        }
+        Ok::<(), String>(())
+    };
+    copy(image_fwi, IMAGE_FILE)?;
+    match picture_fwi {
+        Some(picture_fwi) => copy(picture_fwi, PICTURE_FILE)?,
+        None => {
+            let _ = fs::remove_file(dir.join(PICTURE_FILE));
+        }
+    }
+
+    let contents = toml::to_string_pretty(design)
+        .map_err(|e| format!("Could not save design {}: {}", id, e))?;
+    // Written aside and renamed, so a crash mid-write leaves the previous save intact
+    let design_path = dir.join(DESIGN_FILE);
+    let partial_path = dir.join(format!("{}.partial", DESIGN_FILE));
+    fs::write(&partial_path, contents)
+        .and_then(|()| fs::rename(&partial_path, &design_path))
+        .map_err(|e| format!("Could not write {}: {}", design_path.display(), e))?;
+
+    if is_new {
+        prune_old_designs();
+    }
+    Ok(())
+}
+
+fn read_design(dir: &Path) -> Option<SavedDesign> {
+    let design_path = dir.join(DESIGN_FILE);
+    let contents = fs::read_to_string(&design_path).ok()?;
+    let design = match toml::from_str(&contents) {
+        Ok(design) => design,
+        Err(e) => {
+            println!("Designs: Skipping {}: {}", design_path.display(), e);
+            return None;
+        }
+    };
+    let saved_at = fs::metadata(&design_path)
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or(UNIX_EPOCH);
+    let thumbnail = fs::read(dir.join(IMAGE_FILE))
+        .ok()
+        .and_then(|bytes| fwi::FwiImage::decode(&bytes).ok())
+        .map(|image| image.to_handle());
+
+    Some(SavedDesign {
+        id: dir.file_name()?.to_string_lossy().into_owned(),
+        design,
+        saved_at,
+        thumbnail,
+    })
+}
+
+/// The saved design called `id`.
+pub fn load(id: &str) -> Option<SavedDesign> {
+    read_design(&designs_dir().join(id))
+}
+
+/// Saved designs, most recently edited first.
+pub async fn load_recent() -> Vec<SavedDesign> {
+    tokio::task::spawn_blocking(load_recent_blocking)
+        .await
+        .unwrap_or_else(|e| {
+            println!("Designs: Listing recent designs failed: {}", e);
+            Vec::new()
+        })
+}
+
+fn load_recent_blocking() -> Vec<SavedDesign> {
+    let mut designs: Vec<SavedDesign> = fs::read_dir(designs_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| read_design(&entry.path()))
+        .collect();
+    designs.sort_by_key(|design| Reverse(design.saved_at));
+    designs
+}
+
+fn prune_old_designs() {
+    let mut dirs: Vec<(SystemTime, PathBuf)> = fs::read_dir(designs_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .map(|path| {
+            let saved_at = fs::metadata(path.join(DESIGN_FILE))
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(UNIX_EPOCH);
+            (saved_at, path)
+        })
+        .collect();
+    dirs.sort_by_key(|(saved_at, _)| Reverse(*saved_at));
+    for (_, path) in dirs.into_iter().skip(MAX_SAVED_DESIGNS) {
+        let _ = fs::remove_dir_all(path);
+    }
+}
@@ -0,0 +1,423 @@
+// device.rs
+//
+// Talking to a plugged-in badge through the `fwi-serial` command line tool.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::wasm_apps::{self, BUNDLED_APP_FILE};
+use crate::{app_config, run_log};
+
+/// Every file the wizard writes, i.e. everything a backup has to cover.
+pub fn wizard_files() -> Vec<String> {
+    let paths = &app_config::get().remote_paths;
+    vec![
+        paths.config.clone(),
+        paths.settings.clone(),
+        paths.image.clone(),
+        paths.script(BUNDLED_APP_FILE),
+    ]
+}
+
+/// Whether the wizard may write `remote_path`: one of `wizard_files` or any app
+/// in the scripts folder.
+pub fn is_wizard_file(remote_path: &str) -> bool {
+    let scripts_dir = &app_config::get().remote_paths.scripts_dir;
+    let is_app = remote_path
+        .strip_prefix(scripts_dir.trim_end_matches('/'))
+        .and_then(|rest| rest.strip_prefix('/'))
+        .is_some_and(|name| !name.contains('/') && wasm_apps::is_wasm_file_name(name));
+    is_app || wizard_files().iter().any(|path| path == remote_path)
+}
+
+/// Files pulled off a badge by "Read from badge". Any of them may be missing on
+/// a badge that has never been through the wizard.
+#[derive(Debug, Clone)]
+pub struct DeviceSnapshot {
+    pub config_text: Option<String>,
+    pub settings_text: Option<String>,
+    pub image_fwi: Option<Vec<u8>>,
+    /// Local copy of the image, so it can be uploaded again unchanged.
+    pub image_path: PathBuf,
+}
+
+/// Directory "Read from badge" stores its files in.
+pub fn readback_dir() -> PathBuf {
+    std::env::temp_dir().join("build_a_badge_readback")
+}
+
+/// Directory the Summary screen's comparison stores its files in, kept apart from
+/// [`readback_dir`] whose image may still be queued for upload.
+pub fn comparison_dir() -> PathBuf {
+    std::env::temp_dir().join("build_a_badge_compare")
+}
+
+/// Extra `fwi-serial` arguments for a remote path. Files in the root directory,
+/// like `/settings.txt`, live on the main processor's filesystem, which `-mi 1`
+/// selects.
+fn target_args(remote_path: &str) -> &'static [&'static str] {
+    if remote_path.len() > 1 && remote_path.rfind('/') == Some(0) {
+        &["-mi", "1"]
+    } else {
+        &[]
+    }
+}
+
+/// Runs `fwi-serial` with `args` and returns its stdout. `action` completes the
+/// sentence "Could not ..." in error messages.
+async fn run_fwi_serial<I, S>(args: I, action: &str, timeout: Duration) -> Result<String, String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let args: Vec<S> = args.into_iter().collect();
+    let logged_args: Vec<String> = args
+        .iter()
+        .map(|arg| arg.as_ref().to_string_lossy().into_owned())
+        .collect();
+    let fwi_serial = &app_config::get().fwi_serial;
+    let started = Instant::now();
+    let result = tokio::time::timeout(
+        timeout,
+        tokio::process::Command::new(fwi_serial)
+            .args(&args)
+            .output(),
+    )
+    .await;
+
+    match &result {
+        Ok(Ok(output)) => run_log::record_command(
+            &logged_args,
+            started.elapsed(),
+            output.status.code(),
+            &String::from_utf8_lossy(&output.stdout),
+            &String::from_utf8_lossy(&output.stderr),
+        ),
+        Ok(Err(e)) => {
+            run_log::record_command(&logged_args, started.elapsed(), None, "", &e.to_string())
+        }
+        Err(_) => run_log::record_command(&logged_args, started.elapsed(), None, "", "timed out"),
+    }
+
+    match result {
+        Ok(Ok(output)) if output.status.success() => {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(Ok(output)) => Err(format!(
+            "Could not {}: {}",
+            action,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Ok(Err(e)) => Err(format!(
+            "Could not run fwi-serial ({}): {}",
+            fwi_serial.display(),
+            e
+        )),
+        Err(_) => Err(format!(
+            "Timed out trying to {} ({} seconds) - device may not be connected",
+            action,
+            timeout.as_secs()
+        )),
+    }
+}
+
+/// Copies `local_path` to `remote_path` on the badge.
+pub async fn upload_file(
+    local_path: &Path,
+    remote_path: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    println!(
+        "Device: Uploading {} to {}",
+        local_path.display(),
+        remote_path
+    );
+
+    let mut args = vec![
+        OsStr::new("-s"),
+        local_path.as_os_str(),
+        OsStr::new("-fn"),
+        OsStr::new(remote_path),
+    ];
+    args.extend(target_args(remote_path).iter().map(OsStr::new));
+
+    run_fwi_serial(args, &format!("write {}", remote_path), timeout)
+        .await
+        .map(|_| ())
+}
+
+/// Copies `remote_path` from the badge to `local_path`.
+pub async fn download_file(
+    remote_path: &str,
+    local_path: &Path,
+    timeout: Duration,
+) -> Result<(), String> {
+    println!(
+        "Device: Downloading {} to {}",
+        remote_path,
+        local_path.display()
+    );
+
+    let mut args = vec![
+        OsStr::new("-g"),
+        OsStr::new(remote_path),
+        local_path.as_os_str(),
+    ];
+    args.extend(target_args(remote_path).iter().map(OsStr::new));
+
+    let action = format!("read {}", remote_path);
+    run_fwi_serial(args, &action, timeout).await?;
+
+    // Older fwi-serial builds exit 0 even when the file doesn't exist on the badge
+    if local_path.exists() {
+        Ok(())
+    } else {
+        Err(format!("Could not {}: file not found", action))
+    }
+}
+
+/// Reads `remote_path` back and checks it matches `local_path` byte for byte.
+pub async fn verify_upload(
+    local_path: &Path,
+    remote_path: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let dir = std::env::temp_dir().join("build_a_badge_verify");
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    let readback_path = dir.join(local_path.file_name().unwrap_or(OsStr::new("readback")));
+    let _ = tokio::fs::remove_file(&readback_path).await;
+
+    download_file(remote_path, &readback_path, timeout).await?;
+
+    let (expected, actual) = match (
+        tokio::fs::read(local_path).await,
+        tokio::fs::read(&readback_path).await,
+    ) {
+        (Ok(expected), Ok(actual)) => (expected, actual),
+        (Err(e), _) => return Err(format!("Could not read {}: {}", local_path.display(), e)),
+        (_, Err(e)) => return Err(format!("Could not read {}: {}", readback_path.display(), e)),
+    };
+
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} on the badge doesn't match what was uploaded ({} bytes on the badge, {} expected)",
+            remote_path,
+            actual.len(),
+            expected.len()
+        ))
+    }
+}
+
+/// Starts `file_name` from the badge's scripts folder.
+pub async fn run_app(file_name: &str, timeout: Duration) -> Result<(), String> {
+    println!("Device: Running {}", file_name);
+    run_fwi_serial(
+        [OsStr::new("-w"), OsStr::new(file_name)],
+        &format!("run {}", file_name),
+        timeout,
+    )
+    .await
+    .map(|_| ())
+}
+
+/// One line of a badge directory listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+}
+
+/// Lists `remote_dir` on the badge, directories first.
+pub async fn list_directory(remote_dir: &str) -> Result<Vec<RemoteEntry>, String> {
+    let output = run_fwi_serial(
+        ["--list_directory", remote_dir],
+        &format!("list {}", remote_dir),
+        app_config::get().timeouts.command(),
+    )
+    .await?;
+
+    let mut entries = parse_listing(&output);
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+pub async fn delete_path(remote_path: &str) -> Result<(), String> {
+    println!("Device: Deleting {}", remote_path);
+    run_fwi_serial(
+        ["--remove", remote_path],
+        &format!("delete {}", remote_path),
+        app_config::get().timeouts.command(),
+    )
+    .await
+    .map(|_| ())
+}
+
+pub async fn rename_path(from: &str, to: &str) -> Result<(), String> {
+    println!("Device: Renaming {} to {}", from, to);
+    run_fwi_serial(
+        ["--move", from, to],
+        &format!("rename {}", from),
+        app_config::get().timeouts.command(),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Joins a badge directory and an entry name.
+pub fn join_remote(remote_dir: &str, name: &str) -> String {
+    format!("{}/{}", remote_dir.trim_end_matches('/'), name)
+}
+
+/// The parent of a badge path; the root is its own parent.
+pub fn parent_remote(remote_path: &str) -> String {
+    match remote_path.trim_end_matches('/').rsplit_once('/') {
+        Some(("", _)) | None => "/".to_string(),
+        Some((parent, _)) => parent.to_string(),
+    }
+}
+
+// Listing lines look like `name/` or `d name` for directories and `name 1234` or
+// `f name 1234` for files. Anything that isn't an entry (banners, blank lines) is skipped.
+fn parse_listing(output: &str) -> Vec<RemoteEntry> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.ends_with(':'))
+        .filter_map(|line| {
+            let mut fields: Vec<&str> = line.split_whitespace().collect();
+            let mut is_dir = false;
+            match fields.first() {
+                Some(&"d") | Some(&"D") => {
+                    is_dir = true;
+                    fields.remove(0);
+                }
+                Some(&"f") | Some(&"F") => {
+                    fields.remove(0);
+                }
+                _ => {}
+            }
+
+            let size = match fields.last().and_then(|field| field.parse::<u64>().ok()) {
+                Some(size) if fields.len() > 1 => {
+                    fields.pop();
+                    Some(size)
+                }
+                _ => None,
+            };
+
+            let name = fields.join(" ");
+            let name = match name.strip_suffix('/') {
+                Some(stripped) => {
+                    is_dir = true;
+                    stripped.to_string()
+                }
+                None => name,
+            };
+            if name.is_empty() || name == "." || name == ".." {
+                return None;
+            }
+
+            Some(RemoteEntry { name, is_dir, size })
+        })
+        .collect()
+}
+
+/// Reads the wizard's files back from the connected badge into `dir`.
+pub async fn read_badge(dir: PathBuf) -> Result<DeviceSnapshot, String> {
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+
+    let config_path = dir.join("build_a_badge.txt");
+    let settings_path = dir.join("settings.txt");
+    let image_path = dir.join("build_a_badge.fwi");
+
+    let mut errors = Vec::new();
+    let mut fetch = async |remote: &str, local: &Path| {
+        // Stale copies from an earlier read must not be mistaken for this badge's files
+        let _ = tokio::fs::remove_file(local).await;
+        match download_file(remote, local, app_config::get().timeouts.command()).await {
+            Ok(()) => tokio::fs::read(local).await.ok(),
+            Err(error) => {
+                println!("Device: {}", error);
+                errors.push(error);
+                None
+            }
+        }
+    };
+
+    let remote_paths = &app_config::get().remote_paths;
+    let config_text = fetch(&remote_paths.config, &config_path)
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    let settings_text = fetch(&remote_paths.settings, &settings_path)
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    let image_fwi = fetch(&remote_paths.image, &image_path).await;
+
+    if config_text.is_none() && settings_text.is_none() && image_fwi.is_none() {
+        return Err(errors.join("\n"));
+    }
+
+    Ok(DeviceSnapshot {
+        config_text,
+        settings_text,
+        image_fwi,
+        image_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, is_dir: bool, size: Option<u64>) -> RemoteEntry {
+        RemoteEntry {
+            name: name.to_string(),
+            is_dir,
+            size,
+        }
+    }
+
+    #[test]
+    fn parse_listing_reads_typed_entries() {
+        let output = "Listing /scripts:\n\
+                      d .\n\
+                      d ..\n\
+                      d games\n\
+                      f build_a_badge.wasm 18432\n\
+                      f my app.wasm 512\n";
+        assert_eq!(
+            parse_listing(output),
+            vec![
+                entry("games", true, None),
+                entry("build_a_badge.wasm", false, Some(18432)),
+                entry("my app.wasm", false, Some(512)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_listing_reads_bare_entries() {
+        let output = "\r\nimages/\r\nsettings.txt 214\r\nREADME\r\n";
+        assert_eq!(
+            parse_listing(output),
+            vec![
+                entry("images", true, None),
+                entry("settings.txt", false, Some(214)),
+                entry("README", false, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_listing_keeps_a_number_that_is_the_whole_name() {
+        assert_eq!(parse_listing("2024\n"), vec![entry("2024", false, None)]);
+    }
+}
@@ -0,0 +1,190 @@
+// diff.rs
+//
+// Field-level comparison between what the wizard is about to write and what a
+// connected badge currently has, shown on the Summary screen before flashing.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::badge_config::BadgeConfig;
+use crate::device::DeviceSnapshot;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub field: String,
+    /// `None` when the badge has no value for this field.
+    pub device: Option<String>,
+    pub local: Option<String>,
+}
+
+impl FieldDiff {
+    pub fn is_changed(&self) -> bool {
+        self.device != self.local
+    }
+
+    pub fn device_text(&self) -> &str {
+        self.device.as_deref().unwrap_or("(missing)")
+    }
+
+    pub fn local_text(&self) -> &str {
+        self.local.as_deref().unwrap_or("(removed)")
+    }
+}
+
+/// Compares the generated `build_a_badge.txt`, `settings.txt` and image bytes
+/// with a snapshot read from the badge.
+pub fn diff_configuration(
+    local_config: &BadgeConfig,
+    local_settings_txt: &str,
+    local_image_fwi: Option<&[u8]>,
+    snapshot: &DeviceSnapshot,
+) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+
+    let device_config = snapshot
+        .config_text
+        .as_deref()
+        .and_then(|text| BadgeConfig::parse(text).ok());
+    // Round-tripped through the file, so fallbacks such as the default name
+    // compare as what the badge will actually get
+    let local_config = BadgeConfig::parse(&local_config.to_file_contents())
+        .unwrap_or_else(|_| local_config.clone());
+
+    diffs.push(FieldDiff {
+        field: "Badge name".to_string(),
+        device: device_config.as_ref().map(|config| config.name.clone()),
+        local: Some(local_config.name.clone()),
+    });
+    diffs.push(FieldDiff {
+        field: "LED mode".to_string(),
+        device: device_config
+            .as_ref()
+            .and_then(|config| config.led_mode)
+            .map(|mode| mode.display_name().to_string()),
+        local: local_config
+            .led_mode
+            .map(|mode| mode.display_name().to_string()),
+    });
+
+    let local_settings = key_values(local_settings_txt);
+    let device_settings = snapshot
+        .settings_text
+        .as_deref()
+        .map(key_values)
+        .unwrap_or_default();
+
+    // Keys in the order the wizard writes them, then anything extra found on the badge
+    let mut keys: Vec<&str> = local_settings.iter().map(|(key, _)| key.as_str()).collect();
+    for (key, _) in &device_settings {
+        if !keys.contains(&key.as_str()) {
+            keys.push(key);
+        }
+    }
+
+    let lookup = |pairs: &[(String, String)], key: &str| {
+        pairs
+            .iter()
+            .find(|(candidate, _)| candidate == key)
+            .map(|(_, value)| value.clone())
+    };
+    for key in keys {
+        diffs.push(FieldDiff {
+            field: key.to_string(),
+            device: lookup(&device_settings, key),
+            local: lookup(&local_settings, key),
+        });
+    }
+
+    diffs.push(FieldDiff {
+        field: "Image".to_string(),
+        device: snapshot.image_fwi.as_deref().map(image_fingerprint),
+        local: local_image_fwi.map(image_fingerprint),
+    });
+
+    diffs
+}
+
+// `key=value` lines of a settings file, in file order.
+fn key_values(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+// Short content hash, enough to tell whether the image would change.
+fn image_fingerprint(bytes: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    format!("{:016x} ({} KB)", hasher.finish(), bytes.len() / 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const SETTINGS_TXT: &str = "wifiAPEn=1\nwifiAPssid=Ada-WiLi\nbtEn=1\n";
+
+    fn snapshot(config: Option<&BadgeConfig>, image: Option<&[u8]>) -> DeviceSnapshot {
+        DeviceSnapshot {
+            config_text: config.map(BadgeConfig::to_file_contents),
+            settings_text: config.map(|_| SETTINGS_TXT.to_string()),
+            image_fwi: image.map(<[u8]>::to_vec),
+            image_path: PathBuf::new(),
+        }
+    }
+
+    #[test]
+    fn missing_device_files_show_as_missing() {
+        let config = BadgeConfig::new("Ada", None);
+        let diffs = diff_configuration(
+            &config,
+            SETTINGS_TXT,
+            Some(b"pixels"),
+            &snapshot(None, None),
+        );
+
+        assert!(diffs.iter().all(FieldDiff::is_changed));
+        assert!(diffs.iter().all(|diff| diff.device_text() == "(missing)"));
+        assert_eq!(
+            diffs
+                .iter()
+                .find(|diff| diff.field == "btEn")
+                .unwrap()
+                .local_text(),
+            "1"
+        );
+    }
+
+    #[test]
+    fn identical_configuration_has_no_changes() {
+        let config = BadgeConfig::new("Ada", None);
+        let image = b"pixels".as_slice();
+        let diffs = diff_configuration(
+            &config,
+            SETTINGS_TXT,
+            Some(image),
+            &snapshot(Some(&config), Some(image)),
+        );
+
+        assert!(!diffs.is_empty());
+        assert_eq!(diffs.iter().filter(|diff| diff.is_changed()).count(), 0);
+    }
+
+    #[test]
+    fn changed_image_shows_up() {
+        let config = BadgeConfig::new("Ada", None);
+        let diffs = diff_configuration(
+            &config,
+            SETTINGS_TXT,
+            Some(b"new pixels"),
+            &snapshot(Some(&config), Some(b"old pixels")),
+        );
+
+        let changed: Vec<_> = diffs.iter().filter(|diff| diff.is_changed()).collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].field, "Image");
+    }
+}
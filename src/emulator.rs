@@ -0,0 +1,842 @@
+// emulator.rs
+//
+// Runs a badge WASM app on this computer. The `wiliwasm` host API is mocked:
+// panels and controls are collected into an `EmulatorScreen`, files come from a
+// virtual filesystem holding what the wizard would upload, and the LED show mode
+// is animated by the Emulator screen. Like the serial console, the app runs on a
+// plain thread (wasmi is blocking) bridged into an iced subscription.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::SinkExt;
+use iced::widget::canvas::{self, Cache, Frame, Geometry, Path, Stroke};
+use iced::{Color, Point, Rectangle, Renderer, Size, Subscription, Theme, mouse};
+use tokio::sync::mpsc;
+use wasmi::core::{HostError, TrapCode};
+use wasmi::{Caller, Config, Engine, Error, Extern, Linker, Module, Store, Val};
+
+use crate::fwi::FwiImage;
+
+pub const DISPLAY_WIDTH: f32 = 320.0;
+pub const DISPLAY_HEIGHT: f32 = 240.0;
+pub const LED_COUNT: usize = 7;
+
+/// Size of the canvas `BadgeView` draws into.
+pub const VIEW_WIDTH: f32 = 520.0;
+pub const VIEW_HEIGHT: f32 = 300.0;
+
+const DISPLAY_ORIGIN: Point = Point::new(20.0, 30.0);
+const LED_RING_CENTER: Point = Point::new(440.0, 150.0);
+const LED_RING_RADIUS: f32 = 55.0;
+
+/// Instructions an app may run between two host calls before it is treated as hung.
+const FUEL_PER_SLICE: u64 = 500_000_000;
+
+/// Longest C string read out of the app's memory.
+const MAX_STRING_BYTES: usize = 1024;
+
+/// Returned from a host call once the Emulator screen has closed, to unwind the app.
+#[derive(Debug)]
+struct Stopped;
+
+impl std::fmt::Display for Stopped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Emulator stopped")
+    }
+}
+
+impl HostError for Stopped {}
+
+/// The badge's five buttons, in the order of the firmware's event numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeButton {
+    Gray,
+    Yellow,
+    Green,
+    Blue,
+    Red,
+}
+
+impl BadgeButton {
+    pub const ALL: [BadgeButton; 5] = [
+        BadgeButton::Gray,
+        BadgeButton::Yellow,
+        BadgeButton::Green,
+        BadgeButton::Blue,
+        BadgeButton::Red,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            BadgeButton::Gray => "Gray",
+            BadgeButton::Yellow => "Yellow",
+            BadgeButton::Green => "Green",
+            BadgeButton::Blue => "Blue",
+            BadgeButton::Red => "Red",
+        }
+    }
+
+    fn event_id(&self) -> i32 {
+        *self as i32
+    }
+}
+
+/// Sends button presses to the running app. Cheap to clone into messages.
+#[derive(Debug, Clone)]
+pub struct EmulatorInput(mpsc::UnboundedSender<BadgeButton>);
+
+impl EmulatorInput {
+    pub fn press(&self, button: BadgeButton) {
+        let _ = self.0.send(button);
+    }
+}
+
+#[derive(Clone)]
+pub struct Picture {
+    pub index: i32,
+    pub x: f32,
+    pub y: f32,
+    pub visible: bool,
+    pub image: Option<Arc<FwiImage>>,
+}
+
+// The pixel data is far too long to be worth printing
+impl std::fmt::Debug for Picture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Picture")
+            .field("index", &self.index)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .field("visible", &self.visible)
+            .field("loaded", &self.image.is_some())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TextControl {
+    pub index: i32,
+    pub x: f32,
+    pub y: f32,
+    pub font_size: f32,
+    pub color: [u8; 3],
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Panel {
+    pub index: i32,
+    pub background: [u8; 3],
+    pub pictures: Vec<Picture>,
+    pub texts: Vec<TextControl>,
+}
+
+/// Everything the app has put on the display and LEDs so far.
+#[derive(Debug, Clone, Default)]
+pub struct EmulatorScreen {
+    pub panels: Vec<Panel>,
+    pub shown_panel: Option<i32>,
+    pub led_mode: Option<i32>,
+}
+
+impl EmulatorScreen {
+    pub fn current_panel(&self) -> Option<&Panel> {
+        let shown = self.shown_panel?;
+        self.panels.iter().find(|panel| panel.index == shown)
+    }
+
+    fn panel_mut(&mut self, index: i32) -> Option<&mut Panel> {
+        self.panels.iter_mut().find(|panel| panel.index == index)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum EmulatorEvent {
+    Started(EmulatorInput),
+    Screen(EmulatorScreen),
+    Printed(String),
+    /// The app returned or exited; `Err` for traps and load failures.
+    Exited(Result<String, String>),
+}
+
+/// Badge paths and their contents.
+pub type VirtualFiles = HashMap<String, Vec<u8>>;
+
+/// Runs the app at `wasm_path` for as long as the subscription is alive. Bump
+/// `generation` to start it over.
+pub fn run(
+    wasm_path: PathBuf,
+    files: Arc<VirtualFiles>,
+    generation: u64,
+) -> Subscription<EmulatorEvent> {
+    iced::subscription::channel(
+        (
+            std::any::TypeId::of::<EmulatorInput>(),
+            wasm_path.clone(),
+            generation,
+        ),
+        100,
+        move |mut output| async move {
+            let (events_tx, mut events_rx) = mpsc::unbounded_channel::<EmulatorEvent>();
+            let (buttons_tx, buttons_rx) = mpsc::unbounded_channel::<BadgeButton>();
+            let _ = output
+                .send(EmulatorEvent::Started(EmulatorInput(buttons_tx)))
+                .await;
+
+            println!("Emulator: Running {}", wasm_path.display());
+            // The app thread stops at its next host call once `events_rx` is dropped with this future
+            std::thread::spawn(move || {
+                let result = match std::fs::read(&wasm_path) {
+                    Ok(bytes) => {
+                        execute(&bytes, files, FUEL_PER_SLICE, events_tx.clone(), buttons_rx)
+                    }
+                    Err(e) => Err(format!("Could not read {}: {}", wasm_path.display(), e)),
+                };
+                let _ = events_tx.send(EmulatorEvent::Exited(result));
+            });
+
+            while let Some(event) = events_rx.recv().await {
+                let _ = output.send(event).await;
+            }
+
+            futures::future::pending().await
+        },
+    )
+}
+
+struct Host {
+    files: Arc<VirtualFiles>,
+    open_files: HashMap<i32, VecDeque<String>>,
+    next_handle: i32,
+    screen: EmulatorScreen,
+    events: mpsc::UnboundedSender<EmulatorEvent>,
+    buttons: mpsc::UnboundedReceiver<BadgeButton>,
+    pending_button: Option<BadgeButton>,
+    fuel_per_slice: u64,
+}
+
+impl Host {
+    fn print(&self, message: String) {
+        println!("Emulator: {}", message);
+        let _ = self.events.send(EmulatorEvent::Printed(message));
+    }
+
+    fn screen_changed(&self) {
+        let _ = self.events.send(EmulatorEvent::Screen(self.screen.clone()));
+    }
+
+    /// Apps refer to files both by full path and by bare name.
+    fn find_file(&self, name: &str) -> Option<&Vec<u8>> {
+        let bare = name.trim_start_matches('/');
+        self.files
+            .get(name)
+            .or_else(|| self.files.get(&format!("/{}", bare)))
+            .or_else(|| self.files.get(&format!("/images/{}", bare)))
+    }
+}
+
+fn execute(
+    bytes: &[u8],
+    files: Arc<VirtualFiles>,
+    fuel_per_slice: u64,
+    events: mpsc::UnboundedSender<EmulatorEvent>,
+    buttons: mpsc::UnboundedReceiver<BadgeButton>,
+) -> Result<String, String> {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module =
+        Module::new(&engine, bytes).map_err(|e| format!("Could not load the app: {}", e))?;
+
+    let host = Host {
+        files,
+        open_files: HashMap::new(),
+        next_handle: 1,
+        screen: EmulatorScreen::default(),
+        events,
+        buttons,
+        pending_button: None,
+        fuel_per_slice,
+    };
+    let mut store = Store::new(&engine, host);
+    let _ = store.set_fuel(fuel_per_slice);
+
+    let mut linker = <Linker<Host>>::new(&engine);
+    link_host_api(&mut linker).map_err(|e| format!("Could not set up the host API: {}", e))?;
+
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(|e| format!("Could not start the app: {}", e))?;
+    let entry = ["_start", "main"]
+        .into_iter()
+        .find_map(|name| instance.get_func(&store, name))
+        .ok_or_else(|| "The app exports no _start or main function".to_string())?;
+
+    let mut results = vec![Val::I32(0); entry.ty(&store).results().len()];
+    match entry.call(&mut store, &[], &mut results) {
+        Ok(()) => Ok("The app returned".to_string()),
+        Err(error) => match (error.i32_exit_status(), error.as_trap_code()) {
+            (Some(0), _) => Ok("The app exited to the main menu".to_string()),
+            (Some(status), _) => Err(format!("The app exited with status {}", status)),
+            (_, Some(TrapCode::OutOfFuel)) => Err(
+                "The app ran too long without calling waitms; on the badge it would freeze the UI"
+                    .to_string(),
+            ),
+            _ if error.downcast_ref::<Stopped>().is_some() => Ok(Stopped.to_string()),
+            _ => Err(format!("The app crashed: {}", error)),
+        },
+    }
+}
+
+fn memory_bytes<'a>(caller: &'a Caller<'_, Host>) -> Result<&'a [u8], Error> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Ok(memory.data(caller)),
+        _ => Err(Error::new("The app exports no memory")),
+    }
+}
+
+fn read_string(caller: &Caller<'_, Host>, pointer: i32) -> Result<String, Error> {
+    let memory = memory_bytes(caller)?;
+    let start = pointer as u32 as usize;
+    let tail = memory
+        .get(start..)
+        .ok_or_else(|| Error::new(format!("String pointer {:#x} is out of bounds", start)))?;
+    let end = tail
+        .iter()
+        .take(MAX_STRING_BYTES)
+        .position(|&byte| byte == 0)
+        .unwrap_or(tail.len().min(MAX_STRING_BYTES));
+    Ok(String::from_utf8_lossy(&tail[..end]).into_owned())
+}
+
+fn write_bytes(caller: &mut Caller<'_, Host>, pointer: i32, bytes: &[u8]) -> Result<(), Error> {
+    let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+        return Err(Error::new("The app exports no memory"));
+    };
+    memory
+        .write(caller, pointer as u32 as usize, bytes)
+        .map_err(|e| Error::new(e.to_string()))
+}
+
+/// Called wherever the app yields, so a closed Emulator screen stops it promptly.
+fn yield_point(caller: &mut Caller<'_, Host>) -> Result<(), Error> {
+    if caller.data().events.is_closed() {
+        return Err(Error::host(Stopped));
+    }
+    let fuel = caller.data().fuel_per_slice;
+    let _ = caller.set_fuel(fuel);
+    Ok(())
+}
+
+fn color(red: i32, green: i32, blue: i32) -> [u8; 3] {
+    [red as u8, green as u8, blue as u8]
+}
+
+fn link_host_api(linker: &mut Linker<Host>) -> Result<(), Error> {
+    // wasi-libc start-up: `_start` asks the host to call `main`
+    linker.func_wrap("env", "__main_void", |mut caller: Caller<'_, Host>| {
+        let Some(Extern::Func(main)) = caller.get_export("main") else {
+            return Err(Error::new("The app exports no main function"));
+        };
+        let mut results = vec![Val::I32(0); main.ty(&caller).results().len()];
+        main.call(&mut caller, &[], &mut results)?;
+        Ok(results.first().and_then(Val::i32).unwrap_or(0))
+    })?;
+    linker.func_wrap("env", "__wasm_call_dtors", || {})?;
+    linker.func_wrap(
+        "env",
+        "__wasi_proc_exit",
+        |code: i32| -> Result<(), Error> { Err(Error::i32_exit(code)) },
+    )?;
+
+    linker.func_wrap(
+        "wiliwasm",
+        "addPanel",
+        |mut caller: Caller<'_, Host>,
+         index: i32,
+         _visible: i32,
+         _in_rotation: i32,
+         _use_tile: i32,
+         _tile_id: i32,
+         red: i32,
+         green: i32,
+         blue: i32,
+         _show_menu: i32| {
+            let host = caller.data_mut();
+            host.screen.panels.retain(|panel| panel.index != index);
+            host.screen.panels.push(Panel {
+                index,
+                background: color(red, green, blue),
+                ..Panel::default()
+            });
+            host.screen_changed();
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "addControlPictureFromFile",
+        |mut caller: Caller<'_, Host>,
+         panel: i32,
+         index: i32,
+         x: i32,
+         y: i32,
+         name: i32,
+         visible: i32|
+         -> Result<(), Error> {
+            let name = read_string(&caller, name)?;
+            let host = caller.data_mut();
+            let image = match host.find_file(&name).map(|bytes| FwiImage::decode(bytes)) {
+                Some(Ok(image)) => Some(Arc::new(image)),
+                Some(Err(error)) => {
+                    host.print(format!("Picture {}: {}", name, error));
+                    None
+                }
+                None => {
+                    host.print(format!("Picture {} not found", name));
+                    None
+                }
+            };
+            if let Some(panel) = host.screen.panel_mut(panel) {
+                panel.pictures.retain(|picture| picture.index != index);
+                panel.pictures.push(Picture {
+                    index,
+                    x: x as f32,
+                    y: y as f32,
+                    visible: visible != 0,
+                    image,
+                });
+            }
+            host.screen_changed();
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "addControlText",
+        |mut caller: Caller<'_, Host>,
+         panel: i32,
+         index: i32,
+         x: i32,
+         y: i32,
+         _font: i32,
+         font_size: i32,
+         red: i32,
+         green: i32,
+         blue: i32,
+         text: i32|
+         -> Result<(), Error> {
+            let text = read_string(&caller, text)?;
+            let host = caller.data_mut();
+            if let Some(panel) = host.screen.panel_mut(panel) {
+                panel.texts.retain(|control| control.index != index);
+                panel.texts.push(TextControl {
+                    index,
+                    x: x as f32,
+                    y: y as f32,
+                    // The firmware's sizes are its own units; half of them is close on screen
+                    font_size: (font_size as f32 / 2.0).max(8.0),
+                    color: color(red, green, blue),
+                    text,
+                });
+            }
+            host.screen_changed();
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "setControlValueText",
+        |mut caller: Caller<'_, Host>, panel: i32, index: i32, text: i32| -> Result<(), Error> {
+            let text = read_string(&caller, text)?;
+            let host = caller.data_mut();
+            if let Some(control) = host.screen.panel_mut(panel).and_then(|panel| {
+                panel
+                    .texts
+                    .iter_mut()
+                    .find(|control| control.index == index)
+            }) {
+                control.text = text;
+            }
+            host.screen_changed();
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "showPanel",
+        |mut caller: Caller<'_, Host>, index: i32| {
+            let host = caller.data_mut();
+            host.screen.shown_panel = Some(index);
+            host.screen_changed();
+        },
+    )?;
+    linker.func_wrap("wiliwasm", "exitToMainAppMenu", || -> Result<(), Error> {
+        Err(Error::i32_exit(0))
+    })?;
+
+    linker.func_wrap(
+        "wiliwasm",
+        "openFile",
+        |mut caller: Caller<'_, Host>, name: i32, _mode: i32| -> Result<i32, Error> {
+            let name = read_string(&caller, name)?;
+            let host = caller.data_mut();
+            let Some(contents) = host.find_file(&name) else {
+                host.print(format!("openFile: {} not found", name));
+                return Ok(0);
+            };
+            let lines = String::from_utf8_lossy(contents)
+                .lines()
+                .map(str::to_string)
+                .collect();
+            let handle = host.next_handle;
+            host.next_handle += 1;
+            host.open_files.insert(handle, lines);
+            Ok(handle)
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "readFileLine",
+        |mut caller: Caller<'_, Host>, handle: i32, buffer: i32, size: i32| -> Result<i32, Error> {
+            let line = caller
+                .data_mut()
+                .open_files
+                .get_mut(&handle)
+                .and_then(VecDeque::pop_front);
+            let Some(line) = line else {
+                return Ok(0);
+            };
+            // Truncated like fgets, always leaving room for the terminator
+            let mut bytes: Vec<u8> = line.into_bytes();
+            bytes.truncate((size.max(1) - 1) as usize);
+            let length = bytes.len() as i32;
+            bytes.push(0);
+            write_bytes(&mut caller, buffer, &bytes)?;
+            Ok(length)
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "closeFile",
+        |mut caller: Caller<'_, Host>, handle: i32| {
+            caller.data_mut().open_files.remove(&handle).is_some() as i32
+        },
+    )?;
+
+    linker.func_wrap(
+        "wiliwasm",
+        "printInt",
+        |caller: Caller<'_, Host>,
+         format: i32,
+         _color: i32,
+         _kind: i32,
+         value: i32|
+         -> Result<(), Error> {
+            let format = read_string(&caller, format)?;
+            let message = format.replace("%d", &value.to_string()).replace("%%", "%");
+            caller.data().print(message.trim_matches('\n').to_string());
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "setLEDShowMode",
+        |mut caller: Caller<'_, Host>, mode: i32| {
+            let host = caller.data_mut();
+            host.screen.led_mode = Some(mode);
+            host.screen_changed();
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "waitms",
+        |mut caller: Caller<'_, Host>, milliseconds: i32| -> Result<(), Error> {
+            std::thread::sleep(Duration::from_millis(milliseconds.clamp(0, 10_000) as u64));
+            yield_point(&mut caller)
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "hasEvent",
+        |mut caller: Caller<'_, Host>| -> Result<i32, Error> {
+            yield_point(&mut caller)?;
+            let host = caller.data_mut();
+            if host.pending_button.is_none() {
+                host.pending_button = host.buttons.try_recv().ok();
+            }
+            Ok(host.pending_button.is_some() as i32)
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "getEventData",
+        |mut caller: Caller<'_, Host>, _data: i32| -> i32 {
+            caller
+                .data_mut()
+                .pending_button
+                .take()
+                .map_or(-1, |button| button.event_id())
+        },
+    )?;
+    linker.func_wrap(
+        "wiliwasm",
+        "playSoundFromFrequencyAndDuration",
+        |caller: Caller<'_, Host>, frequency: f32, duration: f32, _amplitude: f32, wait: i32| {
+            caller
+                .data()
+                .print(format!("♪ {:.0} Hz for {:.2} s", frequency, duration));
+            if wait != 0 {
+                std::thread::sleep(Duration::from_secs_f32(duration.clamp(0.0, 10.0)));
+            }
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Colors of the LED ring `elapsed` seconds into a show mode, approximating the
+/// firmware's animations.
+pub fn led_colors(led_mode: Option<crate::LedMode>, elapsed: f32) -> [[f32; 3]; LED_COUNT] {
+    use crate::LedMode;
+
+    let step = (elapsed * 8.0) as usize;
+    let wave = |offset: f32| ((elapsed * 3.0 + offset).sin() + 1.0) / 2.0;
+    let hue = |turns: f32| {
+        let h = turns.rem_euclid(1.0) * 6.0;
+        let x = 1.0 - (h % 2.0 - 1.0).abs();
+        match h as u32 {
+            0 => [1.0, x, 0.0],
+            1 => [x, 1.0, 0.0],
+            2 => [0.0, 1.0, x],
+            3 => [0.0, x, 1.0],
+            4 => [x, 0.0, 1.0],
+            _ => [1.0, 0.0, x],
+        }
+    };
+    // Cheap repeatable noise for the flashing modes
+    let noise = |seed: usize| (seed.wrapping_mul(2_654_435_761) >> 7) % 7;
+    let scale = |rgb: [f32; 3], level: f32| rgb.map(|channel| channel * level);
+
+    let mut leds = [[0.0; 3]; LED_COUNT];
+    for (i, led) in leds.iter_mut().enumerate() {
+        let position = i as f32 / LED_COUNT as f32;
+        let chase = |rgb: [f32; 3]| {
+            let distance = (step + LED_COUNT - i) % LED_COUNT;
+            scale(rgb, 1.0 / (1.0 + distance as f32 * 2.0))
+        };
+        let bar = |level: f32| {
+            if position < level {
+                hue(0.33 - position * 0.33)
+            } else {
+                [0.0; 3]
+            }
+        };
+
+        *led = match led_mode {
+            None | Some(LedMode::Manual) => [0.0; 3],
+            Some(LedMode::Rainbow) => hue(elapsed * 0.2),
+            Some(LedMode::Snowstorm) => scale([1.0; 3], (noise(step + i * 13) == 0) as u8 as f32),
+            Some(LedMode::RedChase) => chase([1.0, 0.0, 0.0]),
+            Some(LedMode::RainbowChase) => hue(position + elapsed * 0.5),
+            Some(LedMode::BlueChase) => chase([0.0, 0.2, 1.0]),
+            Some(LedMode::GreenDot) => {
+                scale([0.0, 1.0, 0.0], (i == step / 8 % LED_COUNT) as u8 as f32)
+            }
+            Some(LedMode::BlueDot) => {
+                scale([0.0, 0.2, 1.0], (i == step / 8 % LED_COUNT) as u8 as f32)
+            }
+            Some(LedMode::BlueSin) => scale([0.0, 0.2, 1.0], wave(position * 6.0)),
+            Some(LedMode::WhiteFade) => scale([1.0; 3], wave(0.0)),
+            Some(LedMode::BarGraph) => bar(wave(0.0)),
+            Some(LedMode::Zylon) => {
+                let sweep = step % (LED_COUNT * 2 - 2);
+                let lit = if sweep < LED_COUNT {
+                    sweep
+                } else {
+                    LED_COUNT * 2 - 2 - sweep
+                };
+                scale([1.0, 0.0, 0.0], (i == lit) as u8 as f32)
+            }
+            // No microphone or accelerometer here; something lively will do
+            Some(LedMode::Audio) => bar(noise(step) as f32 / LED_COUNT as f32 + 0.15),
+            Some(LedMode::Accel) => hue(wave(0.0) * 0.3 + 0.5),
+        };
+    }
+    leds
+}
+
+/// Draws the badge: the display as the app left it, and the LED ring.
+pub struct BadgeView<'a> {
+    pub screen: &'a EmulatorScreen,
+    /// Holds the display layer; clear it whenever `screen` changes.
+    pub display_cache: &'a Cache,
+    pub leds: [[f32; 3]; LED_COUNT],
+}
+
+impl<Message> canvas::Program<Message> for BadgeView<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let display = self.display_cache.draw(renderer, bounds.size(), |frame| {
+            frame.fill_rectangle(Point::ORIGIN, frame.size(), Color::from_rgb8(40, 40, 40));
+            self.draw_display(frame);
+        });
+
+        let mut leds = Frame::new(renderer, bounds.size());
+        for (i, rgb) in self.leds.iter().enumerate() {
+            let angle =
+                i as f32 / LED_COUNT as f32 * std::f32::consts::TAU - std::f32::consts::FRAC_PI_2;
+            let center = Point::new(
+                LED_RING_CENTER.x + LED_RING_RADIUS * angle.cos(),
+                LED_RING_CENTER.y + LED_RING_RADIUS * angle.sin(),
+            );
+            let led = Path::circle(center, 11.0);
+            // Unlit LEDs stay faintly visible so the ring reads as a ring
+            let [r, g, b] = rgb.map(|channel| 0.12 + channel * 0.88);
+            leds.fill(&led, Color::from_rgb(r, g, b));
+            leds.stroke(
+                &led,
+                Stroke::default()
+                    .with_color(Color::from_rgb8(90, 90, 90))
+                    .with_width(2.0),
+            );
+        }
+
+        vec![display, leds.into_geometry()]
+    }
+}
+
+impl BadgeView<'_> {
+    fn draw_display(&self, frame: &mut Frame) {
+        let display_size = Size::new(DISPLAY_WIDTH, DISPLAY_HEIGHT);
+        let Some(panel) = self.screen.current_panel() else {
+            frame.fill_rectangle(DISPLAY_ORIGIN, display_size, Color::BLACK);
+            return;
+        };
+
+        let [r, g, b] = panel.background;
+        frame.fill_rectangle(DISPLAY_ORIGIN, display_size, Color::from_rgb8(r, g, b));
+
+        for picture in panel.pictures.iter().filter(|picture| picture.visible) {
+            if let Some(image) = &picture.image {
+                draw_image(frame, picture.x, picture.y, image);
+            }
+        }
+
+        for control in &panel.texts {
+            let [r, g, b] = control.color;
+            frame.fill_text(canvas::Text {
+                content: control.text.clone(),
+                position: Point::new(DISPLAY_ORIGIN.x + control.x, DISPLAY_ORIGIN.y + control.y),
+                color: Color::from_rgb8(r, g, b),
+                size: control.font_size.into(),
+                ..canvas::Text::default()
+            });
+        }
+    }
+}
+
+// Canvas has no image primitive, so pixels are drawn as runs of equal color
+fn draw_image(frame: &mut Frame, x: f32, y: f32, image: &FwiImage) {
+    if x < 0.0 || y < 0.0 {
+        return;
+    }
+    let visible_width = (DISPLAY_WIDTH - x).min(image.width as f32).max(0.0) as usize;
+    let visible_height = (DISPLAY_HEIGHT - y).min(image.height as f32).max(0.0) as usize;
+
+    for row in 0..visible_height {
+        let pixels = &image.rgba[row * image.width as usize * 4..][..visible_width * 4];
+        let mut start = 0;
+        while start < visible_width {
+            let color = &pixels[start * 4..start * 4 + 4];
+            let mut end = start + 1;
+            while end < visible_width && &pixels[end * 4..end * 4 + 4] == color {
+                end += 1;
+            }
+            frame.fill_rectangle(
+                Point::new(
+                    DISPLAY_ORIGIN.x + x + start as f32,
+                    DISPLAY_ORIGIN.y + y + row as f32,
+                ),
+                Size::new((end - start) as f32, 1.0),
+                Color::from_rgba8(color[0], color[1], color[2], color[3] as f32 / 255.0),
+            );
+            start = end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the bundled app until it has set an LED mode or exited, then closes
+    /// the screen the way the Emulator screen does.
+    fn run_bundled_app(fuel_per_slice: u64) -> (EmulatorScreen, Result<String, String>) {
+        let files: VirtualFiles = [
+            ("/build_a_badge.txt".to_string(), b"Ada\n4\n".to_vec()),
+            (
+                "/images/build_a_badge.fwi".to_string(),
+                include_bytes!("../assets/doge.fwi").to_vec(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        let (_buttons_tx, buttons_rx) = mpsc::unbounded_channel();
+        let app = std::thread::spawn(move || {
+            let bytes = include_bytes!("../build_a_badge.wasm");
+            execute(
+                bytes,
+                Arc::new(files),
+                fuel_per_slice,
+                events_tx,
+                buttons_rx,
+            )
+        });
+
+        let mut screen = EmulatorScreen::default();
+        while let Some(event) = events_rx.blocking_recv() {
+            match event {
+                EmulatorEvent::Screen(update) => screen = update,
+                EmulatorEvent::Exited(result) => return (screen, result),
+                _ => {}
+            }
+            if screen.led_mode.is_some() {
+                break;
+            }
+        }
+        drop(events_rx);
+        (screen, app.join().expect("the app thread panicked"))
+    }
+
+    #[test]
+    fn bundled_app_draws_and_sets_leds() {
+        let (screen, result) = run_bundled_app(FUEL_PER_SLICE);
+
+        let panel = screen.current_panel().expect("no panel shown");
+        assert!(panel.pictures.iter().any(|picture| picture.image.is_some()));
+        assert!(panel.texts.iter().any(|control| control.text == "Ada"));
+        assert_eq!(screen.led_mode, Some(4));
+        assert_eq!(result, Ok(Stopped.to_string()));
+    }
+
+    #[test]
+    fn runaway_app_stops_at_the_fuel_limit() {
+        let (_, result) = run_bundled_app(50);
+
+        let error = result.expect_err("the app should have run out of fuel");
+        assert!(error.contains("without calling waitms"), "{}", error);
+    }
+}
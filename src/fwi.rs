@@ -0,0 +1,154 @@
+// fwi.rs
+//
+// Reader and writer for the badge's `.fwi` image format: a 24-byte header followed by
+// big-endian RGB565 pixels.
+//
+//   0..8    magic "FW01IMG\0"
+//   8..12   format version (u32 LE, currently 1)
+//   12..16  pixel count (u32 LE)
+//   16..18  width (u16 LE)
+//   18..20  height (u16 LE)
+//   20..24  reserved
+
+use iced::widget::image;
+
+const MAGIC: &[u8; 8] = b"FW01IMG\0";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 24;
+
+/// The badge's screen, which every picture is fitted to before upload.
+pub const SCREEN_WIDTH: u32 = 320;
+pub const SCREEN_HEIGHT: u32 = 240;
+
+#[derive(Debug, Clone)]
+pub struct FwiImage {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA8, row major.
+    pub rgba: Vec<u8>,
+}
+
+impl FwiImage {
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_LEN || &bytes[0..8] != MAGIC {
+            return Err("Not a .fwi image (bad header)".to_string());
+        }
+
+        let read_u32 = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+        let read_u16 = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+
+        let pixel_count = read_u32(12) as usize;
+        let width = read_u16(16) as u32;
+        let height = read_u16(18) as u32;
+
+        if pixel_count != (width * height) as usize {
+            return Err(format!(
+                "Corrupt .fwi image: {width}x{height} does not match {pixel_count} pixels"
+            ));
+        }
+        let pixel_data = &bytes[HEADER_LEN..];
+        if pixel_data.len() < pixel_count * 2 {
+            return Err(format!(
+                "Truncated .fwi image: expected {} bytes of pixels, found {}",
+                pixel_count * 2,
+                pixel_data.len()
+            ));
+        }
+
+        let rgba = pixel_data
+            .chunks_exact(2)
+            .take(pixel_count)
+            .flat_map(|pixel| {
+                let value = u16::from_be_bytes([pixel[0], pixel[1]]);
+                let expand = |component: u16, bits: u32| {
+                    let max = (1u16 << bits) - 1;
+                    (component as u32 * 255 / max as u32) as u8
+                };
+                [
+                    expand(value >> 11, 5),
+                    expand((value >> 5) & 0x3f, 6),
+                    expand(value & 0x1f, 5),
+                    255,
+                ]
+            })
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    /// Scales `picture` to fit the badge's screen, centered on black.
+    pub fn from_picture(picture: &::image::DynamicImage) -> Self {
+        let scaled = picture
+            .resize(
+                SCREEN_WIDTH,
+                SCREEN_HEIGHT,
+                ::image::imageops::FilterType::Lanczos3,
+            )
+            .to_rgba8();
+        let mut screen = ::image::RgbaImage::from_pixel(
+            SCREEN_WIDTH,
+            SCREEN_HEIGHT,
+            ::image::Rgba([0, 0, 0, 255]),
+        );
+        ::image::imageops::overlay(
+            &mut screen,
+            &scaled,
+            ((SCREEN_WIDTH - scaled.width()) / 2) as i64,
+            ((SCREEN_HEIGHT - scaled.height()) / 2) as i64,
+        );
+
+        Self {
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+            rgba: screen.into_raw(),
+        }
+    }
+
+    /// The `.fwi` file for this image. Alpha is dropped.
+    pub fn encode(&self) -> Vec<u8> {
+        let pixel_count = self.width * self.height;
+        let mut bytes = Vec::with_capacity(HEADER_LEN + pixel_count as usize * 2);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&pixel_count.to_le_bytes());
+        bytes.extend_from_slice(&(self.width as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.height as u16).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+
+        for pixel in self.rgba.chunks_exact(4) {
+            let [r, g, b] = [pixel[0] as u16, pixel[1] as u16, pixel[2] as u16];
+            let value = ((r >> 3) << 11) | ((g >> 2) << 5) | (b >> 3);
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn to_handle(&self) -> image::Handle {
+        image::Handle::from_pixels(self.width, self.height, self.rgba.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOGE: &[u8] = include_bytes!("../assets/doge.fwi");
+
+    #[test]
+    fn encode_round_trips_a_decoded_image() {
+        let decoded = FwiImage::decode(DOGE).unwrap();
+        assert_eq!(
+            (decoded.width, decoded.height),
+            (SCREEN_WIDTH, SCREEN_HEIGHT)
+        );
+
+        let encoded = decoded.encode();
+        assert_eq!(encoded[..HEADER_LEN], DOGE[..HEADER_LEN]);
+        assert_eq!(encoded.len(), DOGE.len());
+        assert_eq!(FwiImage::decode(&encoded).unwrap().rgba, decoded.rgba);
+    }
+}
@@ -0,0 +1,233 @@
+// image_library.rs
+//
+// A folder of pictures offered on the Customize Badge screen next to the theme
+// pack's gallery, for events with more art than fits in a row, and the
+// conversion every picture from outside the gallery goes through, whether
+// picked here, dropped onto the window or pasted. Scanning and conversion run on
+// a blocking thread so a folder of large photos doesn't freeze the wizard, and
+// both cache their output by file path, size and modification time:
+//
+//   <cache>/thumbnails/<key>.png   small previews for the picker
+//   <cache>/converted/<key>.fwi    pictures as uploaded to the badge
+//   <cache>/pasted/*.png           clipboard pictures, saved so they convert like files
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use iced::widget::image;
+
+use crate::{app_config, fwi};
+
+const THUMBNAIL_WIDTH: u32 = 160;
+const THUMBNAIL_HEIGHT: u32 = 120;
+
+/// Clipboard pictures kept before the oldest are deleted.
+const MAX_PASTED: usize = 20;
+
+const EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "bmp", "gif", "webp"];
+
+#[derive(Debug, Clone)]
+pub struct LibraryImage {
+    pub path: PathBuf,
+    /// File name without the extension, which is what search matches.
+    pub name: String,
+    pub thumbnail: image::Handle,
+}
+
+impl LibraryImage {
+    /// The full-size picture, as shown in the preview once picked.
+    pub fn handle(&self) -> image::Handle {
+        image::Handle::from_path(&self.path)
+    }
+}
+
+/// Whether `path` has an extension the wizard can convert.
+pub fn is_picture(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// `image_library` from the app config, or `images` in the wizard's data folder.
+pub fn library_dir() -> PathBuf {
+    let configured = app_config::get().image_library.trim();
+    if configured.is_empty() {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("build_a_badge")
+            .join("images")
+    } else {
+        PathBuf::from(configured)
+    }
+}
+
+pub fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("build_a_badge")
+}
+
+/// Changes whenever the file at `path` does, so stale cache entries are never used.
+fn cache_key(path: &Path) -> Result<String, String> {
+    let metadata =
+        fs::metadata(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+/// Lists the pictures in `library_dir()` by name, making thumbnails as needed.
+pub async fn scan() -> Result<Vec<LibraryImage>, String> {
+    tokio::task::spawn_blocking(scan_blocking)
+        .await
+        .map_err(|e| format!("Image library scan failed: {}", e))?
+}
+
+fn scan_blocking() -> Result<Vec<LibraryImage>, String> {
+    let dir = library_dir();
+    // The default folder is made on first use so there's somewhere obvious to put pictures
+    if app_config::get().image_library.trim().is_empty() {
+        let _ = fs::create_dir_all(&dir);
+    }
+    let entries =
+        fs::read_dir(&dir).map_err(|e| format!("Could not open {}: {}", dir.display(), e))?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_picture(path))
+        .collect();
+    paths.sort();
+
+    let thumbnails_dir = cache_dir().join("thumbnails");
+    let _ = fs::create_dir_all(&thumbnails_dir);
+    let images: Vec<LibraryImage> = paths
+        .into_iter()
+        .filter_map(|path| match thumbnail(&path, &thumbnails_dir) {
+            Ok(thumbnail) => Some(LibraryImage {
+                name: path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                path,
+                thumbnail,
+            }),
+            Err(error) => {
+                println!("Image library: Skipping {}", error);
+                None
+            }
+        })
+        .collect();
+
+    println!(
+        "Image library: Found {} pictures in {}",
+        images.len(),
+        dir.display()
+    );
+    Ok(images)
+}
+
+fn thumbnail(path: &Path, thumbnails_dir: &Path) -> Result<image::Handle, String> {
+    let cached = thumbnails_dir.join(format!("{}.png", cache_key(path)?));
+    if !cached.exists() {
+        let picture = ::image::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        // Renamed into place like a conversion, so a half-written thumbnail is never reused
+        let partial_path = cached.with_extension("png.partial");
+        picture
+            .thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT)
+            .save_with_format(&partial_path, ::image::ImageFormat::Png)
+            .map_err(|e| e.to_string())
+            .and_then(|()| fs::rename(&partial_path, &cached).map_err(|e| e.to_string()))
+            .map_err(|e| format!("{}: {}", cached.display(), e))?;
+    }
+    Ok(image::Handle::from_path(cached))
+}
+
+/// The `.fwi` for the picture at `path`, converting it on first use.
+pub async fn convert(path: PathBuf) -> Result<PathBuf, String> {
+    tokio::task::spawn_blocking(move || convert_blocking(&path))
+        .await
+        .map_err(|e| format!("Converting the picture failed: {}", e))?
+}
+
+fn convert_blocking(path: &Path) -> Result<PathBuf, String> {
+    let converted_dir = cache_dir().join("converted");
+    fs::create_dir_all(&converted_dir)
+        .map_err(|e| format!("Could not create {}: {}", converted_dir.display(), e))?;
+    let fwi_path = converted_dir.join(format!("{}.fwi", cache_key(path)?));
+    if fwi_path.exists() {
+        return Ok(fwi_path);
+    }
+
+    let picture =
+        ::image::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    let bytes = fwi::FwiImage::from_picture(&picture).encode();
+    // Written aside and renamed, so an interrupted conversion is never mistaken for a finished one
+    let partial_path = fwi_path.with_extension("fwi.partial");
+    fs::write(&partial_path, bytes)
+        .and_then(|()| fs::rename(&partial_path, &fwi_path))
+        .map_err(|e| format!("Could not write {}: {}", fwi_path.display(), e))?;
+
+    println!("Image library: Converted {}", path.display());
+    Ok(fwi_path)
+}
+
+/// Saves the picture on the clipboard as a PNG, ready for `convert`.
+pub async fn save_clipboard_image() -> Result<PathBuf, String> {
+    tokio::task::spawn_blocking(save_clipboard_image_blocking)
+        .await
+        .map_err(|e| format!("Pasting failed: {}", e))?
+}
+
+fn save_clipboard_image_blocking() -> Result<PathBuf, String> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("Could not open the clipboard: {}", e))?;
+    let pasted = clipboard
+        .get_image()
+        .map_err(|_| "There's no picture on the clipboard to paste".to_string())?;
+    let picture = ::image::RgbaImage::from_raw(
+        pasted.width as u32,
+        pasted.height as u32,
+        pasted.bytes.into_owned(),
+    )
+    .ok_or_else(|| "The clipboard picture is damaged".to_string())?;
+
+    let pasted_dir = cache_dir().join("pasted");
+    fs::create_dir_all(&pasted_dir)
+        .map_err(|e| format!("Could not create {}: {}", pasted_dir.display(), e))?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_millis())
+        .unwrap_or(0);
+    let path = pasted_dir.join(format!("pasted-{}.png", now));
+    prune_pasted(&pasted_dir);
+    picture
+        .save(&path)
+        .map_err(|e| format!("Could not save {}: {}", path.display(), e))?;
+
+    println!(
+        "Image library: Pasted {}x{} picture",
+        pasted.width, pasted.height
+    );
+    Ok(path)
+}
+
+fn prune_pasted(pasted_dir: &Path) {
+    // Names sort by paste time
+    let mut paths: Vec<PathBuf> = fs::read_dir(pasted_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    let excess = (paths.len() + 1).saturating_sub(MAX_PASTED);
+    for path in paths.into_iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+}
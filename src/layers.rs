@@ -0,0 +1,296 @@
+// layers.rs
+//
+// Decorations drawn over the badge picture, so booth visitors can dress up a
+// photo without an outside editor. Frames cover the whole screen and are
+// usually see-through in the middle; stickers are placed, scaled and turned by
+// hand. Both are PNGs with alpha from the theme pack (see `theme_pack.rs`).
+//
+// A design keeps its layers by decoration name, bottom first, and they're
+// flattened onto the screen-sized picture just before it's packed into a
+// `.fwi`, so the picture underneath can still be swapped or adjusted.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use ::image::RgbaImage;
+use ::image::imageops::{self, FilterType};
+use serde::{Deserialize, Serialize};
+
+use crate::fwi::{self, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::theme_pack::ThemePack;
+
+/// A sticker's longer side at scale 1.0, in screen pixels.
+pub const STICKER_SIZE: f32 = 80.0;
+pub const MIN_SCALE: f32 = 0.25;
+pub const MAX_SCALE: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerKind {
+    Frame,
+    Sticker,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    pub kind: LayerKind,
+    /// Decoration name in the theme pack.
+    pub name: String,
+    /// Center of a sticker on the screen, in pixels. Frames ignore this,
+    /// `scale` and `rotation`.
+    pub x: f32,
+    pub y: f32,
+    /// `MIN_SCALE` to `MAX_SCALE` times `STICKER_SIZE`.
+    pub scale: f32,
+    /// Degrees clockwise.
+    pub rotation: f32,
+}
+
+// By bits, since the floats only ever come from sliders and saved designs
+impl Hash for Layer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.kind.hash(state);
+        self.name.hash(state);
+        for value in [self.x, self.y, self.scale, self.rotation] {
+            value.to_bits().hash(state);
+        }
+    }
+}
+
+impl Layer {
+    /// A layer showing the decoration called `name`, centered on the screen.
+    pub fn new(kind: LayerKind, name: &str) -> Self {
+        Self {
+            kind,
+            name: name.to_string(),
+            x: SCREEN_WIDTH as f32 / 2.0,
+            y: SCREEN_HEIGHT as f32 / 2.0,
+            scale: 1.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+type DecodeCache = HashMap<(LayerKind, PathBuf), Arc<RgbaImage>>;
+
+/// Decorations already decoded and sized for the screen. Frames are stretched
+/// to it; stickers are shrunk to the largest they can be drawn.
+static DECODED: LazyLock<Mutex<DecodeCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn decoded(kind: LayerKind, path: &Path) -> Result<Arc<RgbaImage>, String> {
+    let key = (kind, path.to_path_buf());
+    if let Some(decoded) = DECODED
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&key).cloned())
+    {
+        return Ok(decoded);
+    }
+
+    let picture =
+        ::image::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    let sized = match kind {
+        LayerKind::Frame => picture
+            .resize_exact(SCREEN_WIDTH, SCREEN_HEIGHT, FilterType::Lanczos3)
+            .to_rgba8(),
+        LayerKind::Sticker => {
+            let largest = (STICKER_SIZE * MAX_SCALE) as u32;
+            picture
+                .resize(largest, largest, FilterType::Lanczos3)
+                .to_rgba8()
+        }
+    };
+    let sized = Arc::new(sized);
+    if let Ok(mut cache) = DECODED.lock() {
+        cache.insert(key, sized.clone());
+    }
+    Ok(sized)
+}
+
+/// Draws `layers` onto `canvas`, bottom first.
+pub fn flatten(
+    canvas: &mut fwi::FwiImage,
+    layers: &[Layer],
+    pack: &ThemePack,
+) -> Result<(), String> {
+    for layer in layers {
+        let decoration = pack
+            .find_decoration(layer.kind, &layer.name)
+            .ok_or_else(|| format!("\"{}\" is not in the {} theme", layer.name, pack.name))?;
+        let decoded = decoded(layer.kind, &decoration.path)?;
+        match layer.kind {
+            LayerKind::Frame => {
+                for (target, source) in canvas.rgba.chunks_exact_mut(4).zip(decoded.pixels()) {
+                    blend(target, source.0.map(|channel| channel as f32));
+                }
+            }
+            LayerKind::Sticker => draw_sticker(canvas, &decoded, layer),
+        }
+    }
+    Ok(())
+}
+
+fn draw_sticker(canvas: &mut fwi::FwiImage, sticker: &RgbaImage, layer: &Layer) {
+    // Scaled with a proper filter first, so only the turn is sampled per pixel
+    let longer_side = sticker.width().max(sticker.height()).max(1) as f32;
+    let factor = STICKER_SIZE * layer.scale.clamp(MIN_SCALE, MAX_SCALE) / longer_side;
+    let width = ((sticker.width() as f32 * factor).round() as u32).max(1);
+    let height = ((sticker.height() as f32 * factor).round() as u32).max(1);
+    let scaled = imageops::resize(sticker, width, height, FilterType::Triangle);
+
+    let (sin, cos) = layer.rotation.to_radians().sin_cos();
+    let (half_width, half_height) = (width as f32 / 2.0, height as f32 / 2.0);
+    // Half the size of the box the turned sticker fits in
+    let reach_x = half_width * cos.abs() + half_height * sin.abs();
+    let reach_y = half_width * sin.abs() + half_height * cos.abs();
+
+    let first_x = (layer.x - reach_x).floor().max(0.0) as u32;
+    let last_x = ((layer.x + reach_x).ceil().max(0.0) as u32).min(canvas.width);
+    let first_y = (layer.y - reach_y).floor().max(0.0) as u32;
+    let last_y = ((layer.y + reach_y).ceil().max(0.0) as u32).min(canvas.height);
+
+    for y in first_y..last_y {
+        for x in first_x..last_x {
+            // Screen pixel center, turned back into the sticker
+            let dx = x as f32 + 0.5 - layer.x;
+            let dy = y as f32 + 0.5 - layer.y;
+            let source_x = dx * cos + dy * sin + half_width - 0.5;
+            let source_y = -dx * sin + dy * cos + half_height - 0.5;
+            if let Some(color) = sample(&scaled, source_x, source_y) {
+                let at = ((y * canvas.width + x) * 4) as usize;
+                blend(&mut canvas.rgba[at..at + 4], color);
+            }
+        }
+    }
+}
+
+/// Bilinear sample at `(x, y)`, straight (not premultiplied) alpha. Outside the
+/// sticker counts as transparent, which softens its edges.
+fn sample(picture: &RgbaImage, x: f32, y: f32) -> Option<[f32; 4]> {
+    let (left, top) = (x.floor(), y.floor());
+    if left < -1.0 || top < -1.0 || left >= picture.width() as f32 || top >= picture.height() as f32
+    {
+        return None;
+    }
+    let (fraction_x, fraction_y) = (x - left, y - top);
+
+    let mut premultiplied = [0.0f32; 4];
+    for (offset_x, offset_y, weight) in [
+        (0, 0, (1.0 - fraction_x) * (1.0 - fraction_y)),
+        (1, 0, fraction_x * (1.0 - fraction_y)),
+        (0, 1, (1.0 - fraction_x) * fraction_y),
+        (1, 1, fraction_x * fraction_y),
+    ] {
+        let (px, py) = (left as i64 + offset_x, top as i64 + offset_y);
+        if px < 0 || py < 0 || px >= picture.width() as i64 || py >= picture.height() as i64 {
+            continue;
+        }
+        let [r, g, b, a] = picture
+            .get_pixel(px as u32, py as u32)
+            .0
+            .map(|channel| channel as f32);
+        let weight = weight * a / 255.0;
+        premultiplied[0] += r * weight;
+        premultiplied[1] += g * weight;
+        premultiplied[2] += b * weight;
+        premultiplied[3] += weight;
+    }
+
+    let alpha = premultiplied[3];
+    (alpha > 0.0).then(|| {
+        [
+            premultiplied[0] / alpha,
+            premultiplied[1] / alpha,
+            premultiplied[2] / alpha,
+            alpha * 255.0,
+        ]
+    })
+}
+
+/// `source` over the opaque `target` pixel.
+fn blend(target: &mut [u8], source: [f32; 4]) {
+    let alpha = source[3] / 255.0;
+    for (channel, value) in target.iter_mut().zip(source).take(3) {
+        *channel = (value * alpha + *channel as f32 * (1.0 - alpha)).round() as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BACKGROUND: [u8; 4] = [10, 20, 30, 255];
+    const RED: [u8; 4] = [255, 0, 0, 255];
+
+    fn canvas() -> fwi::FwiImage {
+        fwi::FwiImage {
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+            rgba: BACKGROUND.repeat((SCREEN_WIDTH * SCREEN_HEIGHT) as usize),
+        }
+    }
+
+    fn sticker_at(x: f32, y: f32, rotation: f32) -> Layer {
+        Layer {
+            x,
+            y,
+            rotation,
+            ..Layer::new(LayerKind::Sticker, "test")
+        }
+    }
+
+    /// The smallest box holding every pixel the sticker changed, as
+    /// `(left, top, right, bottom)` with the right and bottom exclusive.
+    fn changed_box(canvas: &fwi::FwiImage) -> (u32, u32, u32, u32) {
+        let mut changed = (u32::MAX, u32::MAX, 0, 0);
+        for (index, pixel) in canvas.rgba.chunks_exact(4).enumerate() {
+            if pixel != BACKGROUND {
+                let (x, y) = (index as u32 % canvas.width, index as u32 / canvas.width);
+                changed.0 = changed.0.min(x);
+                changed.1 = changed.1.min(y);
+                changed.2 = changed.2.max(x + 1);
+                changed.3 = changed.3.max(y + 1);
+            }
+        }
+        changed
+    }
+
+    #[test]
+    fn unturned_sticker_covers_exactly_its_box() {
+        let size = STICKER_SIZE as u32;
+        let sticker = RgbaImage::from_pixel(size, size, ::image::Rgba(RED));
+        let mut canvas = canvas();
+        draw_sticker(&mut canvas, &sticker, &sticker_at(100.0, 80.0, 0.0));
+
+        assert_eq!(changed_box(&canvas), (60, 40, 140, 120));
+        for (index, pixel) in canvas.rgba.chunks_exact(4).enumerate() {
+            let (x, y) = (index as u32 % canvas.width, index as u32 / canvas.width);
+            if (60..140).contains(&x) && (40..120).contains(&y) {
+                assert_eq!(pixel, RED, "at {}, {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn quarter_turn_swaps_width_and_height() {
+        let (width, height) = (STICKER_SIZE as u32, STICKER_SIZE as u32 / 2);
+        let sticker = RgbaImage::from_pixel(width, height, ::image::Rgba(RED));
+        let mut canvas = canvas();
+        draw_sticker(&mut canvas, &sticker, &sticker_at(100.0, 80.0, 90.0));
+
+        let (left, top, right, bottom) = changed_box(&canvas);
+        assert_eq!((right - left, bottom - top), (height, width));
+    }
+
+    #[test]
+    fn transparent_pixels_leave_the_canvas_alone() {
+        let mut target = BACKGROUND;
+        blend(&mut target, [255.0, 255.0, 255.0, 0.0]);
+        assert_eq!(target, BACKGROUND);
+
+        let transparent = RgbaImage::from_pixel(4, 4, ::image::Rgba([255, 255, 255, 0]));
+        assert_eq!(sample(&transparent, 1.5, 1.5), None);
+    }
+}
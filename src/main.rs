@@ -1,6 +1,33 @@
 // main.rs
+mod app_config;
+mod adjust;
+mod app_log;
+mod assets;
+mod backup;
+mod badge_config;
+mod bundle;
+mod cli;
+mod config_error;
+mod design;
+mod device;
+mod diff;
+mod emulator;
+mod fwi;
+mod image_library;
+mod layers;
+mod pipeline;
+mod qr;
+mod run_log;
+mod serial_console;
+mod settings;
+mod theme_pack;
+mod validation;
+mod wasm_apps;
+mod wasm_check;
+
 use iced::widget::{
-    Space, button, column, container, image, progress_bar, radio, row, text, text_input,
+    Space, button, canvas, checkbox, column, container, image, pick_list, progress_bar, radio,
+    row, scrollable, slider, text, text_input,
 };
 use iced::window;
 use iced::{
@@ -8,8 +35,27 @@ use iced::{
     Subscription, Theme, executor, event, mouse, keyboard,
 };
 use std::fs;
-use std::sync::LazyLock;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+
+use adjust::{AdjustFailure, AdjustedPicture, Adjustments};
+use app_log::{LogLevel, LogLine, LogParser};
+use assets::Asset;
+use backup::BackupKind;
+use badge_config::BadgeConfig;
+use config_error::ConfigFailure;
+use design::{Design, SavedDesign};
+use device::{DeviceSnapshot, RemoteEntry};
+use diff::FieldDiff;
+use emulator::{BadgeButton, EmulatorEvent, EmulatorInput, EmulatorScreen};
+use image_library::LibraryImage;
+use layers::{Layer, LayerKind};
+use pipeline::{ConfigJob, ConfigStep};
+use serial_console::{ConsoleEvent, ConsoleWriter, PortChoice};
+use settings::{DeviceSettings, SettingsFlag, SettingsPreset};
+use theme_pack::PackChoice;
+use wasm_apps::WasmApp;
 
 // Explicitly import necessary types and traits for Iced 0.12.1
 use iced::widget::button::{Appearance as ButtonAppearance, StyleSheet as ButtonStyleSheet};
@@ -17,44 +63,77 @@ use iced::widget::container::{
     Appearance as ContainerAppearance, StyleSheet as ContainerStyleSheet,
 };
 // --- Constants and Statics ---
+// Warnings in the badge output; the brand colors come from the theme pack
 static YELLOW: LazyLock<Color> = LazyLock::new(|| Color::from_rgb8(255, 191, 0));
-static BLUE_TEXT: LazyLock<Color> = LazyLock::new(|| Color::from_rgb8(0, 85, 150));
-
-// --- Static handles for your images ---
-static BADGE_PLACEHOLDER_IMAGE: LazyLock<image::Handle> = LazyLock::new(|| {
-    image::Handle::from_memory(include_bytes!("../assets/badge_placeholder.png").to_vec())
-});
-static STUFF_ME_ICON: LazyLock<image::Handle> = LazyLock::new(|| {
-    image::Handle::from_memory(include_bytes!("../assets/stuff_me_icon.png").to_vec())
-});
-static LIGHT_ME_ICON: LazyLock<image::Handle> = LazyLock::new(|| {
-    image::Handle::from_memory(include_bytes!("../assets/light_me_icon.png").to_vec())
-});
-static PLACE_ME_ICON: LazyLock<image::Handle> = LazyLock::new(|| {
-    image::Handle::from_memory(include_bytes!("../assets/place_me_icon.png").to_vec())
-});
-static APP_LOGO_IMAGE: LazyLock<image::Handle> =
-    LazyLock::new(|| image::Handle::from_memory(include_bytes!("../assets/logo.png").to_vec()));
-static NAME_ME_IMAGE: LazyLock<image::Handle> = LazyLock::new(|| {
-    image::Handle::from_memory(include_bytes!("../assets/badge_placeholder.png").to_vec())
-});
-
-static DEFAULT_PLACEHOLDER_IMAGE: LazyLock<image::Handle> = LazyLock::new(|| {
-    image::Handle::from_memory(include_bytes!("../assets/badge_placeholder.png").to_vec())
-});
-
-// --- NEW: Static handles for user-selectable images ---
-static DEFCON_LOGO_IMAGE: LazyLock<image::Handle> = LazyLock::new(|| {
-    image::Handle::from_memory(include_bytes!("../assets/defcon_logo.png").to_vec())
-});
-static DOGE_IMAGE: LazyLock<image::Handle> =
-    LazyLock::new(|| image::Handle::from_memory(include_bytes!("../assets/doge.png").to_vec()));
-static PUPPY_IMAGE: LazyLock<image::Handle> =
-    LazyLock::new(|| image::Handle::from_memory(include_bytes!("../assets/puppy.png").to_vec()));
-static PIP_BOY_IMAGE: LazyLock<image::Handle> =
-    LazyLock::new(|| image::Handle::from_memory(include_bytes!("../assets/pip_boy.jpg").to_vec()));
-static VEGAS_IMAGE: LazyLock<image::Handle> =
-    LazyLock::new(|| image::Handle::from_memory(include_bytes!("../assets/vegas.png").to_vec()));
+
+// --- Images: the wizard's artwork is in assets.rs, the gallery in theme_pack.rs ---
+/// A gallery picture by its asset name, as used for `default_image` in the app config.
+fn gallery_image(name: &str) -> Option<image::Handle> {
+    theme_pack::active()
+        .find_named(name)
+        .map(|gallery| gallery.handle.clone())
+}
+
+/// What to draw for a picture handle. Gallery handles only identify the picture;
+/// the decoded copy comes from `assets`.
+fn shown_picture(handle: &image::Handle) -> image::Handle {
+    match theme_pack::active().find(handle) {
+        Some(gallery) => assets::picture(&gallery.source_path),
+        None => handle.clone(),
+    }
+}
+
+/// The asset name of a gallery picture.
+fn gallery_name(handle: &image::Handle) -> Option<String> {
+    theme_pack::active()
+        .find(handle)
+        .map(|gallery| gallery.name.clone())
+}
+
+/// A copy of a saved design's picture to work from. Autosave writes the design's
+/// own files, which would otherwise be edited again on every save.
+fn opened_design_image(saved: &SavedDesign, picture_path: &Path) -> PathBuf {
+    let dir = image_library::cache_dir().join("opened");
+    let copy = dir.join(format!("{}.fwi", saved.id));
+    match fs::create_dir_all(&dir).and_then(|()| fs::copy(picture_path, &copy)) {
+        Ok(_) => copy,
+        Err(e) => {
+            println!("Designs: Could not copy {}: {}", picture_path.display(), e);
+            picture_path.to_path_buf()
+        }
+    }
+}
+
+/// What a new design starts with: the theme pack's choice, else the app config's.
+fn default_gallery_image() -> Option<image::Handle> {
+    theme_pack::active()
+        .default_image
+        .as_deref()
+        .and_then(gallery_image)
+        .or_else(|| gallery_image(&app_config::get().default_image))
+}
+
+fn default_led_mode() -> Option<LedMode> {
+    theme_pack::active()
+        .default_led_mode
+        .or_else(|| app_config::get().led_mode())
+        .or(Some(LedMode::Accel))
+}
+
+/// Buttons and selection highlights, from the theme pack.
+fn accent_color() -> Color {
+    theme_pack::active().accent
+}
+
+/// Headings and status messages, from the theme pack.
+fn text_color() -> Color {
+    theme_pack::active().text
+}
+
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(2);
+const LIBRARY_COLUMNS: usize = 6;
+const LIBRARY_PAGE_SIZE: usize = LIBRARY_COLUMNS * 2;
+const RECENT_DESIGNS_SHOWN: usize = 6;
 
 const HEADING_SIZE: u16 = 30;
 const BODY_SIZE: u16 = 18;
@@ -64,6 +143,14 @@ const BUTTON_TEXT_SIZE: u16 = 24;
 
 // Text input ID for focus management
 const BADGE_NAME_INPUT_ID: &str = "badge_name_input";
+const CONSOLE_INPUT_ID: &str = "console_input";
+const CONSOLE_SCROLL_ID: &str = "console_scroll";
+const APP_LOG_SCROLL_ID: &str = "app_log_scroll";
+const EMULATOR_SCROLL_ID: &str = "emulator_scroll";
+
+// Only the tail of the scrollback is laid out; the full buffer still goes to capture files
+const CONSOLE_VISIBLE_LINES: usize = 400;
+const CONSOLE_VISIBLE_HEX_BYTES: usize = 4096;
 
 // --- Application State and Messages ---
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -72,7 +159,12 @@ enum AppScreen {
     CustomizeBadge,
     CustomizeLeds,
     NameBadge,
+    ChooseApp,
     Summary,
+    AdvancedSettings,
+    FileBrowser,
+    Console,
+    Emulator,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -94,6 +186,23 @@ enum LedMode {
 }
 
 impl LedMode {
+    const ALL: [LedMode; 14] = [
+        LedMode::Manual,
+        LedMode::Rainbow,
+        LedMode::Snowstorm,
+        LedMode::RedChase,
+        LedMode::RainbowChase,
+        LedMode::BlueChase,
+        LedMode::GreenDot,
+        LedMode::BlueDot,
+        LedMode::BlueSin,
+        LedMode::WhiteFade,
+        LedMode::BarGraph,
+        LedMode::Zylon,
+        LedMode::Audio,
+        LedMode::Accel,
+    ];
+
     fn display_name(&self) -> &'static str {
         match self {
             LedMode::Manual => "Manual",
@@ -131,6 +240,24 @@ impl LedMode {
             LedMode::Accel => 13,
         }
     }
+
+    fn from_integer(value: u8) -> Option<LedMode> {
+        LedMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_integer() == value)
+    }
+
+    /// A mode by number or display name, ignoring case and spaces.
+    fn from_name(name: &str) -> Option<LedMode> {
+        let wanted = name.trim();
+        if let Ok(number) = wanted.parse() {
+            return LedMode::from_integer(number);
+        }
+        let normalize = |name: &str| name.replace(' ', "").to_lowercase();
+        LedMode::ALL
+            .into_iter()
+            .find(|mode| normalize(mode.display_name()) == normalize(wanted))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -139,18 +266,133 @@ enum AppScreenTransition {
     // Removed FadingOut and FadingIn since we have instant transitions
 }
 
+/// A picture from outside the theme pack's gallery.
+#[derive(Debug, Clone)]
+struct ImportedImage {
+    handle: image::Handle,
+    /// What gets uploaded.
+    fwi_path: PathBuf,
+    /// The picture the `.fwi` was converted from, if it's still around.
+    source_path: Option<PathBuf>,
+}
+
 struct BuildABadgeApp {
     current_screen: AppScreen,
     selected_customize_image: Option<image::Handle>,
     selected_led_mode: Option<LedMode>,
     badge_name: String,
+    device_settings: DeviceSettings,
+    wifi_qr_code: Option<image::Handle>,
+    /// Bundled apps first, then any the user added.
+    wasm_apps: Vec<WasmApp>,
+    selected_wasm_app: usize,
+    wasm_app_error: Option<String>,
 
     // Configuration state
     is_configuring: bool,
     configuration_progress: f32,
     configuration_status: String,
-    configuration_error: Option<String>,
+    configuration_error: Option<ConfigFailure>,
     configuration_console_output: String,
+    // This run's file under run_log::logs_dir()
+    run_log_path: Option<PathBuf>,
+    run_log_status: Option<Result<String, String>>,
+    // Outcome of the last .badge export or import
+    bundle_status: Option<Result<String, String>>,
+
+    // Read-back state for "Read from badge"
+    is_reading_device: bool,
+    device_read_error: Option<String>,
+    device_snapshot: Option<DeviceSnapshot>,
+    // A picture from outside the gallery (read off a badge, a restored design, or
+    // picked, dropped or pasted)
+    imported_image: Option<ImportedImage>,
+    // Sliders and decorations on the Customize Badge screen, and the last picture
+    // they made; see `render_picture`
+    adjustments: Adjustments,
+    layers: Vec<Layer>,
+    selected_layer: Option<usize>,
+    adjusted_picture: Option<AdjustedPicture>,
+    adjust_error: Option<AdjustFailure>,
+    is_rendering_picture: bool,
+
+    // Summary screen comparison against the connected badge
+    is_comparing_device: bool,
+    comparison_error: Option<String>,
+    comparison_snapshot: Option<DeviceSnapshot>,
+    configuration_diff: Option<Vec<FieldDiff>>,
+
+    // Manual backup / restore
+    is_backing_up: bool,
+    backup_status: Option<Result<String, String>>,
+
+    // Badge file browser
+    browser_path: String,
+    browser_entries: Vec<RemoteEntry>,
+    browser_selected: Option<String>,
+    browser_rename_to: String,
+    browser_confirm_delete: bool,
+    is_browser_busy: bool,
+    browser_status: Option<Result<String, String>>,
+
+    // Serial console
+    console_return_screen: AppScreen,
+    console_ports: Vec<PortChoice>,
+    console_port: Option<PortChoice>,
+    console_connect_requested: bool,
+    console_writer: Option<ConsoleWriter>,
+    console_buffer: Vec<u8>,
+    console_hex_view: bool,
+    console_input: String,
+    console_history: Vec<String>,
+    console_history_index: Option<usize>,
+    console_capture: Option<(PathBuf, fs::File)>,
+    console_status: Option<String>,
+
+    // Output from the badge's app after a successful configuration, read over the
+    // console's serial connection
+    app_log_streaming: bool,
+    app_log_parser: Option<LogParser>,
+    app_log_lines: Vec<LogLine>,
+    /// While paused, only this many lines are shown; new ones keep arriving behind it.
+    app_log_paused_at: Option<usize>,
+
+    // Local emulator for the selected WASM app
+    emulator_running: bool,
+    /// Bumped to restart the app from scratch.
+    emulator_generation: u64,
+    emulator_files: Arc<emulator::VirtualFiles>,
+    emulator_input: Option<EmulatorInput>,
+    emulator_screen: EmulatorScreen,
+    emulator_display_cache: canvas::Cache,
+    emulator_started_at: Instant,
+    emulator_led_elapsed: f32,
+    emulator_output: Vec<String>,
+    emulator_status: Option<Result<String, String>>,
+
+    // Autosave of the design being edited; `design_id` is None until it first differs
+    // from `blank_design`
+    design_id: Option<String>,
+    blank_design: Design,
+    last_saved_design: Option<(Design, PathBuf)>,
+    recent_designs: Vec<SavedDesign>,
+
+    // Pictures from image_library::library_dir(), scanned the first time they're shown
+    library_images: Vec<LibraryImage>,
+    library_scanned: bool,
+    is_scanning_library: bool,
+    library_error: Option<String>,
+    library_search: String,
+    library_page: usize,
+    // Picture (picked, dropped or pasted) being converted to .fwi; the preview
+    // switches once it's ready
+    converting_picture: Option<PathBuf>,
+    picture_error: Option<String>,
+    is_file_hovering: bool,
+
+    // Event branding; the active pack itself lives in theme_pack::active()
+    theme_packs: Vec<PackChoice>,
+    theme_pack_error: Option<String>,
 
     // Animation state (simplified for instant transitions)
     transition: AppScreenTransition,
@@ -161,16 +403,105 @@ struct BuildABadgeApp {
 enum Message {
     NavigateTo(AppScreen),
     SelectCustomizeImage(image::Handle),
+    AdjustPicture(Adjustments),
+    PictureRendered(Result<AdjustedPicture, AdjustFailure>),
+    AddLayer(LayerKind, String),
+    SelectLayer(usize),
+    ChangeLayer(usize, Layer),
+    RaiseLayer(usize),
+    LowerLayer(usize),
+    RemoveLayer(usize),
+    LibraryRescan,
+    LibraryScanned(Result<Vec<LibraryImage>, String>),
+    LibrarySearch(String),
+    LibraryPage(usize),
+    ConvertPicture(PathBuf),
+    PictureConverted(PathBuf, Result<PathBuf, String>),
+    FileHovered(bool),
+    FileDropped(PathBuf),
+    PasteImage,
+    ImagePasted(Result<PathBuf, String>),
     SelectLedMode(LedMode),
+    SelectWasmApp(usize),
+    AddWasmApp,
+    AddWasmAppSelected(Option<PathBuf>),
     BadgeNameChanged(String),
+    SelectSettingsPreset(SettingsPreset),
+    ToggleSetting(SettingsFlag, bool),
+    WifiSsidChanged(String),
+    WifiPassphraseChanged(String),
+    GenerateWifiPassphrase,
+    BluetoothNameChanged(String),
+    DefaultScriptChanged(String),
+    ReadFromBadge,
+    BadgeRead(Result<DeviceSnapshot, String>),
+    CompareWithBadge,
+    ComparisonRead(Result<DeviceSnapshot, String>),
+    BackupBadge,
+    BackupFinished(Result<PathBuf, String>),
+    RestoreBackup,
+    RestoreBackupSelected(Option<PathBuf>),
+    RestoreFinished(Result<String, String>),
+    BrowserOpen(String),
+    BrowserListed(String, Result<Vec<RemoteEntry>, String>),
+    BrowserSelect(String),
+    BrowserRenameChanged(String),
+    BrowserRename,
+    BrowserDelete,
+    BrowserDownload,
+    BrowserDownloadTo(Option<PathBuf>),
+    BrowserUpload,
+    BrowserUploadFrom(Option<PathBuf>),
+    BrowserActionFinished(Result<String, String>),
+    ConsoleRefreshPorts,
+    ConsoleSelectPort(PortChoice),
+    ConsoleConnect,
+    ConsoleDisconnect,
+    ConsoleEvent(ConsoleEvent),
+    ConsoleInputChanged(String),
+    ConsoleSend,
+    ConsoleSendInterrupt,
+    ConsoleToggleHex(bool),
+    ConsoleClear,
+    ConsoleStartCapture,
+    ConsoleCaptureTo(Option<PathBuf>),
+    ConsoleStopCapture,
+    AppLogTogglePause,
+    AppLogClear,
+    AppLogStop,
+    RunEmulator,
+    EmulatorEvent(EmulatorEvent),
+    EmulatorButton(BadgeButton),
+    EmulatorStop,
+    EmulatorTick(Instant),
     StartConfiguration,
     ConfigurationStepUpdate(String, f32), // step description, progress (0.0-1.0)
-    ConfigurationComplete(Result<String, String>),
+    ConfigurationComplete(Result<String, ConfigFailure>),
+    AutosaveDesign,
+    RecentDesignsLoaded(Vec<SavedDesign>),
+    AssetsDecoded,
+    OpenDesign(usize),
+    NewDesign,
+    SelectThemePack(PackChoice),
+    ExportBundle,
+    ExportBundleTo(Option<PathBuf>),
+    ImportBundle,
+    ImportBundleFrom(Option<PathBuf>),
+    SaveRunLog,
+    SaveRunLogTo(Option<PathBuf>),
+    OpenLogsFolder,
     MouseButtonPressed(iced::mouse::Button),
     KeyPressed(iced::keyboard::Key),
 }
 
 pub fn main() -> iced::Result {
+    // `build_a_badge apply <bundle>` configures a badge without opening a window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(exit_code) = cli::run(&args) {
+        std::process::exit(exit_code);
+    }
+    app_config::AppConfig::write_defaults();
+
     let settings = Settings {
         window: window::Settings {
             size: Size::new(1200.0, 900.0),
@@ -191,48 +522,192 @@ impl Application for BuildABadgeApp {
     type Flags = ();
 
     fn new(_flags: Self::Flags) -> (Self, Command<Message>) {
+        // Before anything picks a default picture or LED mode
+        let theme_pack_error = theme_pack::load_configured().err();
+
         // The initial application state
         let app_state = Self {
             current_screen: AppScreen::Welcome,
-            selected_customize_image: None,
-            selected_led_mode: Some(LedMode::Accel), // Default to Accelerometer
+            selected_customize_image: default_gallery_image(),
+            selected_led_mode: default_led_mode(),
             badge_name: String::new(),
+            device_settings: DeviceSettings::default(),
+            wifi_qr_code: None,
+            wasm_apps: WasmApp::bundled(),
+            selected_wasm_app: 0,
+            wasm_app_error: None,
 
             is_configuring: false,
             configuration_progress: 0.0,
             configuration_status: String::new(),
             configuration_error: None,
             configuration_console_output: String::new(),
+            run_log_path: None,
+            run_log_status: None,
+            bundle_status: None,
+
+            is_reading_device: false,
+            device_read_error: None,
+            device_snapshot: None,
+            imported_image: None,
+            adjustments: Adjustments::default(),
+            layers: Vec::new(),
+            selected_layer: None,
+            adjusted_picture: None,
+            adjust_error: None,
+            is_rendering_picture: false,
+
+            is_comparing_device: false,
+            comparison_error: None,
+            comparison_snapshot: None,
+            configuration_diff: None,
+
+            is_backing_up: false,
+            backup_status: None,
+
+            browser_path: "/".to_string(),
+            browser_entries: Vec::new(),
+            browser_selected: None,
+            browser_rename_to: String::new(),
+            browser_confirm_delete: false,
+            is_browser_busy: false,
+            browser_status: None,
+
+            console_return_screen: AppScreen::Welcome,
+            console_ports: Vec::new(),
+            console_port: None,
+            console_connect_requested: false,
+            console_writer: None,
+            console_buffer: Vec::new(),
+            console_hex_view: false,
+            console_input: String::new(),
+            console_history: Vec::new(),
+            console_history_index: None,
+            console_capture: None,
+            console_status: None,
+
+            app_log_streaming: false,
+            app_log_parser: None,
+            app_log_lines: Vec::new(),
+            app_log_paused_at: None,
+
+            emulator_running: false,
+            emulator_generation: 0,
+            emulator_files: Arc::default(),
+            emulator_input: None,
+            emulator_screen: EmulatorScreen::default(),
+            emulator_display_cache: canvas::Cache::new(),
+            emulator_started_at: Instant::now(),
+            emulator_led_elapsed: 0.0,
+            emulator_output: Vec::new(),
+            emulator_status: None,
+
+            design_id: None,
+            blank_design: Design::default(),
+            last_saved_design: None,
+            recent_designs: Vec::new(),
+
+            library_images: Vec::new(),
+            library_scanned: false,
+            is_scanning_library: false,
+            library_error: None,
+            library_search: String::new(),
+            library_page: 0,
+            converting_picture: None,
+            picture_error: None,
+            is_file_hovering: false,
+
+            theme_packs: theme_pack::available(),
+            theme_pack_error,
 
             transition: AppScreenTransition::Idle,
             current_opacity: 1.0,
         };
+        let mut app_state = app_state;
+        app_state.blank_design = app_state.current_design();
 
         (
             app_state,
-            Command::none(), // No initial commands, subscriptions are in `subscription()`
+            Command::batch([
+                Command::perform(assets::decode_all(), |()| Message::AssetsDecoded),
+                Command::perform(
+                    assets::decode_pictures(theme_pack::active().pictures()),
+                    |()| Message::AssetsDecoded,
+                ),
+                Command::perform(design::load_recent(), Message::RecentDesignsLoaded),
+            ]),
         )
     }
 
     fn title(&self) -> String {
-        String::from("Build-A-Badge")
+        let pack = theme_pack::active();
+        match pack.dir {
+            Some(_) => format!("Build-A-Badge - {}", pack.name),
+            None => String::from("Build-A-Badge"),
+        }
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::NavigateTo(screen) => {
                 if self.current_screen != screen {
+                    // The console is a detour from any screen; Back returns to where it was opened
+                    if screen == AppScreen::Console {
+                        self.console_return_screen = self.current_screen;
+                    }
+
+                    // The emulator only runs while it can be seen
+                    if screen != AppScreen::Emulator {
+                        self.emulator_running = false;
+                        self.emulator_input = None;
+                    }
+
                     // Instant navigation - no transitions
                     self.current_screen = screen;
                     self.transition = AppScreenTransition::Idle;
                     self.current_opacity = 1.0;
 
                     // Clear configuration status when navigating away from summary
-                    if screen != AppScreen::Summary {
+                    // (the advanced settings and console screens are detours that return to it)
+                    if !matches!(
+                        screen,
+                        AppScreen::Summary
+                            | AppScreen::AdvancedSettings
+                            | AppScreen::Console
+                            | AppScreen::Emulator
+                    ) {
                         self.configuration_status = String::new();
                         self.configuration_error = None;
                         self.configuration_progress = 0.0;
                         self.configuration_console_output = String::new();
+                        self.stop_app_log();
+                        self.app_log_lines.clear();
+                    }
+
+                    // The design may have changed since the last comparison
+                    if screen == AppScreen::Summary {
+                        self.refresh_configuration_diff();
+                    }
+
+                    if screen == AppScreen::Welcome {
+                        self.autosave_design();
+                        return Command::perform(design::load_recent(), Message::RecentDesignsLoaded);
+                    }
+
+                    if screen == AppScreen::Console {
+                        return Command::batch([
+                            self.update(Message::ConsoleRefreshPorts),
+                            text_input::focus(text_input::Id::new(CONSOLE_INPUT_ID)),
+                        ]);
+                    }
+
+                    if screen == AppScreen::CustomizeBadge && !self.library_scanned {
+                        return self.update(Message::LibraryRescan);
+                    }
+
+                    // Always show a fresh listing when the file browser opens
+                    if screen == AppScreen::FileBrowser {
+                        return self.update(Message::BrowserOpen(self.browser_path.clone()));
                     }
 
                     // Focus the text input when navigating to the name badge screen
@@ -245,24 +720,672 @@ impl Application for BuildABadgeApp {
             }
             Message::SelectCustomizeImage(handle) => {
                 self.selected_customize_image = Some(handle);
+                return self.render_picture();
+            }
+            Message::AdjustPicture(adjustments) => {
+                self.adjustments = adjustments;
+                return self.render_picture();
+            }
+            Message::PictureRendered(result) => {
+                self.is_rendering_picture = false;
+                match result {
+                    Ok(picture) => {
+                        self.adjusted_picture = Some(picture);
+                        self.adjust_error = None;
+                    }
+                    Err(failure) => {
+                        println!("Adjustments: {}", failure.3);
+                        self.adjust_error = Some(failure);
+                    }
+                }
+                // The sliders may have moved on while this one was rendering
+                return self.render_picture();
+            }
+            Message::AddLayer(kind, name) => {
+                self.layers.push(Layer::new(kind, &name));
+                self.selected_layer = Some(self.layers.len() - 1);
+                return self.render_picture();
+            }
+            Message::SelectLayer(index) => {
+                self.selected_layer = (index < self.layers.len()).then_some(index);
+            }
+            Message::ChangeLayer(index, layer) => {
+                if let Some(existing) = self.layers.get_mut(index) {
+                    *existing = layer;
+                }
+                return self.render_picture();
+            }
+            Message::RaiseLayer(index) => {
+                if index + 1 < self.layers.len() {
+                    self.layers.swap(index, index + 1);
+                    self.selected_layer = Some(index + 1);
+                }
+                return self.render_picture();
+            }
+            Message::LowerLayer(index) => {
+                if index > 0 && index < self.layers.len() {
+                    self.layers.swap(index, index - 1);
+                    self.selected_layer = Some(index - 1);
+                }
+                return self.render_picture();
+            }
+            Message::RemoveLayer(index) => {
+                if index < self.layers.len() {
+                    self.layers.remove(index);
+                    self.selected_layer = None;
+                }
+                return self.render_picture();
+            }
+            Message::LibraryRescan => {
+                if !self.is_scanning_library {
+                    self.is_scanning_library = true;
+                    self.library_scanned = true;
+                    return Command::perform(image_library::scan(), Message::LibraryScanned);
+                }
+            }
+            Message::LibraryScanned(result) => {
+                self.is_scanning_library = false;
+                match result {
+                    Ok(images) => {
+                        self.library_images = images;
+                        self.library_error = None;
+                    }
+                    Err(error) => {
+                        self.library_images.clear();
+                        self.library_error = Some(error);
+                    }
+                }
+                self.library_page = 0;
+            }
+            Message::LibrarySearch(search) => {
+                self.library_search = search;
+                self.library_page = 0;
+            }
+            Message::LibraryPage(page) => {
+                self.library_page = page;
+            }
+            Message::ConvertPicture(path) => {
+                self.converting_picture = Some(path.clone());
+                self.picture_error = None;
+                return Command::perform(image_library::convert(path.clone()), move |result| {
+                    Message::PictureConverted(path.clone(), result)
+                });
+            }
+            Message::PictureConverted(path, result) => {
+                // Only the most recent pick counts if several were clicked in a row
+                if self.converting_picture.as_ref() == Some(&path) {
+                    self.converting_picture = None;
+                    match result {
+                        Ok(fwi_path) => {
+                            let handle = image::Handle::from_path(&path);
+                            self.selected_customize_image = Some(handle.clone());
+                            self.imported_image = Some(ImportedImage {
+                                handle,
+                                fwi_path,
+                                source_path: Some(path),
+                            });
+                            return self.render_picture();
+                        }
+                        Err(error) => self.picture_error = Some(error),
+                    }
+                }
+            }
+            Message::FileHovered(hovering) => {
+                self.is_file_hovering = hovering;
+            }
+            Message::FileDropped(path) => {
+                self.is_file_hovering = false;
+                // Pictures only go on the badge from the picture step
+                if self.current_screen == AppScreen::CustomizeBadge {
+                    if image_library::is_picture(&path) {
+                        return self.update(Message::ConvertPicture(path));
+                    }
+                    self.picture_error = Some(format!(
+                        "{} is not a picture the wizard can use",
+                        path.display()
+                    ));
+                }
+            }
+            Message::PasteImage => {
+                if self.current_screen == AppScreen::CustomizeBadge {
+                    self.picture_error = None;
+                    return Command::perform(
+                        image_library::save_clipboard_image(),
+                        Message::ImagePasted,
+                    );
+                }
+            }
+            Message::ImagePasted(Ok(path)) => {
+                return self.update(Message::ConvertPicture(path));
+            }
+            Message::ImagePasted(Err(error)) => {
+                self.picture_error = Some(error);
             }
             Message::SelectLedMode(mode) => {
                 self.selected_led_mode = Some(mode);
             }
+            Message::SelectWasmApp(index) => {
+                // defscript follows the app unless it was pointed somewhere else by hand
+                let previous_path = self.wasm_app().remote_path();
+                self.selected_wasm_app = index.min(self.wasm_apps.len() - 1);
+                if self.device_settings.default_script == previous_path {
+                    self.device_settings.default_script = self.wasm_app().remote_path();
+                }
+            }
+            Message::AddWasmApp => {
+                let dialog = rfd::AsyncFileDialog::new().add_filter("WebAssembly app", &["wasm"]);
+                return Command::perform(
+                    async move { dialog.pick_file().await.map(|file| file.path().to_path_buf()) },
+                    Message::AddWasmAppSelected,
+                );
+            }
+            Message::AddWasmAppSelected(Some(path)) => match WasmApp::from_file(&path) {
+                Ok(app) => {
+                    self.wasm_app_error = None;
+                    let index = match self
+                        .wasm_apps
+                        .iter()
+                        .position(|existing| existing.local_path == app.local_path)
+                    {
+                        Some(index) => index,
+                        None => {
+                            self.wasm_apps.push(app);
+                            self.wasm_apps.len() - 1
+                        }
+                    };
+                    return self.update(Message::SelectWasmApp(index));
+                }
+                Err(error) => {
+                    println!("WASM app: {}", error);
+                    self.wasm_app_error = Some(error);
+                }
+            },
+            Message::AddWasmAppSelected(None) => {}
             Message::BadgeNameChanged(name) => {
-                // Filter to alphanumeric characters only and limit to 20 characters
-                let filtered_name: String = name.chars().filter(|c| c.is_alphanumeric()).collect();
+                // Keep exactly what was typed; problems are shown inline by the NameBadge screen
+                self.badge_name = name;
+                self.refresh_wifi_qr_code();
+            }
+            Message::SelectSettingsPreset(preset) => {
+                self.device_settings.apply_preset(preset);
+                self.ensure_wifi_passphrase();
+            }
+            Message::ToggleSetting(flag, value) => {
+                self.device_settings.set_flag(flag, value);
+                self.ensure_wifi_passphrase();
+            }
+            Message::WifiSsidChanged(ssid) => {
+                self.device_settings.wifi_ap_ssid = ssid;
+                self.refresh_wifi_qr_code();
+            }
+            Message::WifiPassphraseChanged(passphrase) => {
+                self.device_settings.wifi_ap_passphrase = passphrase;
+                self.refresh_wifi_qr_code();
+            }
+            Message::GenerateWifiPassphrase => {
+                self.device_settings.wifi_ap_passphrase = settings::generate_passphrase();
+                self.refresh_wifi_qr_code();
+            }
+            Message::BluetoothNameChanged(name) => {
+                self.device_settings.bluetooth_name = name;
+            }
+            Message::DefaultScriptChanged(path) => {
+                self.device_settings.default_script = path;
+            }
+            Message::ReadFromBadge => {
+                self.is_reading_device = true;
+                self.device_read_error = None;
+                return Command::perform(
+                    device::read_badge(device::readback_dir()),
+                    Message::BadgeRead,
+                );
+            }
+            Message::BadgeRead(result) => {
+                self.is_reading_device = false;
+                match result {
+                    Ok(snapshot) => {
+                        self.apply_device_snapshot(&snapshot);
+                        self.device_snapshot = Some(snapshot);
+                        return self.update(Message::NavigateTo(AppScreen::CustomizeBadge));
+                    }
+                    Err(error) => {
+                        println!("Read from badge failed: {}", error);
+                        self.device_read_error = Some(error);
+                    }
+                }
+            }
+            Message::CompareWithBadge => {
+                self.is_comparing_device = true;
+                self.comparison_error = None;
+                return Command::perform(
+                    device::read_badge(device::comparison_dir()),
+                    Message::ComparisonRead,
+                );
+            }
+            Message::ComparisonRead(result) => {
+                self.is_comparing_device = false;
+                match result {
+                    Ok(snapshot) => {
+                        self.comparison_snapshot = Some(snapshot);
+                        self.refresh_configuration_diff();
+                    }
+                    Err(error) => {
+                        println!("Compare with badge failed: {}", error);
+                        self.comparison_error = Some(error);
+                        self.comparison_snapshot = None;
+                        self.configuration_diff = None;
+                    }
+                }
+            }
+            Message::BackupBadge => {
+                self.is_backing_up = true;
+                self.backup_status = None;
+                self.bundle_status = None;
+                return Command::perform(
+                    backup::backup_badge(BackupKind::Manual, self.wasm_app().remote_path()),
+                    Message::BackupFinished,
+                );
+            }
+            Message::BackupFinished(result) => {
+                self.is_backing_up = false;
+                self.backup_status =
+                    Some(result.map(|path| format!("Backup saved to {}", path.display())));
+            }
+            Message::RestoreBackup => {
+                let dialog = rfd::AsyncFileDialog::new()
+                    .add_filter("Badge backup", &["tar"])
+                    .set_directory(backup::backups_dir());
+                return Command::perform(
+                    async move { dialog.pick_file().await.map(|file| file.path().to_path_buf()) },
+                    Message::RestoreBackupSelected,
+                );
+            }
+            Message::RestoreBackupSelected(Some(path)) => {
+                self.is_backing_up = true;
+                self.backup_status = None;
+                self.bundle_status = None;
+                return Command::perform(
+                    async move { backup::restore_backup(&path).await },
+                    Message::RestoreFinished,
+                );
+            }
+            Message::RestoreBackupSelected(None) => {}
+            Message::RestoreFinished(result) => {
+                self.is_backing_up = false;
+                self.backup_status = Some(result);
+                // Whatever was compared before is no longer on the badge
+                self.comparison_snapshot = None;
+                self.configuration_diff = None;
+            }
+            Message::BrowserOpen(path) => {
+                self.is_browser_busy = true;
+                self.browser_confirm_delete = false;
+                return Command::perform(
+                    async move {
+                        let result = device::list_directory(&path).await;
+                        (path, result)
+                    },
+                    |(path, result)| Message::BrowserListed(path, result),
+                );
+            }
+            Message::BrowserListed(path, result) => {
+                self.is_browser_busy = false;
+                match result {
+                    Ok(entries) => {
+                        if path != self.browser_path {
+                            self.browser_selected = None;
+                            self.browser_rename_to.clear();
+                        }
+                        self.browser_path = path;
+                        self.browser_entries = entries;
+                    }
+                    Err(error) => self.browser_status = Some(Err(error)),
+                }
+            }
+            Message::BrowserSelect(name) => {
+                let entry = self.browser_entries.iter().find(|entry| entry.name == name);
+                if let Some(entry) = entry.filter(|entry| entry.is_dir) {
+                    // Selecting an already selected directory opens it
+                    if self.browser_selected.as_deref() == Some(name.as_str()) {
+                        let path = device::join_remote(&self.browser_path, &entry.name);
+                        return self.update(Message::BrowserOpen(path));
+                    }
+                }
+                self.browser_rename_to = name.clone();
+                self.browser_selected = Some(name);
+                self.browser_confirm_delete = false;
+            }
+            Message::BrowserRenameChanged(name) => {
+                self.browser_rename_to = name;
+            }
+            Message::BrowserRename => {
+                if let Some(selected) = &self.browser_selected {
+                    let from = device::join_remote(&self.browser_path, selected);
+                    let to = device::join_remote(&self.browser_path, &self.browser_rename_to);
+                    self.is_browser_busy = true;
+                    return Command::perform(
+                        async move {
+                            device::rename_path(&from, &to)
+                                .await
+                                .map(|()| format!("Renamed {} to {}", from, to))
+                        },
+                        Message::BrowserActionFinished,
+                    );
+                }
+            }
+            Message::BrowserDelete => {
+                if let Some(selected) = &self.browser_selected {
+                    if !self.browser_confirm_delete {
+                        self.browser_confirm_delete = true;
+                        return Command::none();
+                    }
+                    let path = device::join_remote(&self.browser_path, selected);
+                    self.is_browser_busy = true;
+                    self.browser_confirm_delete = false;
+                    self.browser_selected = None;
+                    return Command::perform(
+                        async move {
+                            device::delete_path(&path)
+                                .await
+                                .map(|()| format!("Deleted {}", path))
+                        },
+                        Message::BrowserActionFinished,
+                    );
+                }
+            }
+            Message::BrowserDownload => {
+                if let Some(selected) = &self.browser_selected {
+                    let dialog = rfd::AsyncFileDialog::new().set_file_name(selected.clone());
+                    return Command::perform(
+                        async move { dialog.save_file().await.map(|file| file.path().to_path_buf()) },
+                        Message::BrowserDownloadTo,
+                    );
+                }
+            }
+            Message::BrowserDownloadTo(Some(local_path)) => {
+                if let Some(selected) = &self.browser_selected {
+                    let remote_path = device::join_remote(&self.browser_path, selected);
+                    self.is_browser_busy = true;
+                    return Command::perform(
+                        async move {
+                            device::download_file(&remote_path, &local_path, app_config::get().timeouts.command())
+                                .await
+                                .map(|()| format!("Saved {} to {}", remote_path, local_path.display()))
+                        },
+                        Message::BrowserActionFinished,
+                    );
+                }
+            }
+            Message::BrowserDownloadTo(None) => {}
+            Message::BrowserUpload => {
+                return Command::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .pick_file()
+                            .await
+                            .map(|file| file.path().to_path_buf())
+                    },
+                    Message::BrowserUploadFrom,
+                );
+            }
+            Message::BrowserUploadFrom(Some(local_path)) => {
+                let Some(file_name) = local_path.file_name().map(|name| name.to_string_lossy().into_owned()) else {
+                    return Command::none();
+                };
+                let remote_path = device::join_remote(&self.browser_path, &file_name);
+                self.is_browser_busy = true;
+                return Command::perform(
+                    async move {
+                        device::upload_file(&local_path, &remote_path, app_config::get().timeouts.command())
+                            .await
+                            .map(|()| format!("Uploaded {}", remote_path))
+                    },
+                    Message::BrowserActionFinished,
+                );
+            }
+            Message::BrowserUploadFrom(None) => {}
+            Message::BrowserActionFinished(result) => {
+                self.is_browser_busy = false;
+                if let Err(error) = &result {
+                    println!("File browser: {}", error);
+                }
+                self.browser_status = Some(result);
+                return self.update(Message::BrowserOpen(self.browser_path.clone()));
+            }
+            Message::ConsoleRefreshPorts => {
+                self.console_ports = serial_console::available_ports();
+                let still_present = self
+                    .console_port
+                    .as_ref()
+                    .is_some_and(|port| self.console_ports.contains(port));
+                if !still_present && !self.console_connect_requested {
+                    self.console_port = self.console_ports.first().cloned();
+                }
+            }
+            Message::ConsoleSelectPort(port) => {
+                if self.console_port.as_ref() != Some(&port) {
+                    self.console_connect_requested = false;
+                    self.console_writer = None;
+                    self.stop_app_log();
+                }
+                self.console_port = Some(port);
+            }
+            Message::ConsoleConnect => {
+                self.console_connect_requested = true;
+                // Already open if the badge output panel is streaming
+                if self.console_writer.is_none() {
+                    self.console_status = Some("Connecting...".to_string());
+                }
+            }
+            Message::ConsoleDisconnect => {
+                self.console_connect_requested = false;
+                self.console_writer = None;
+                self.stop_app_log();
+                self.console_status = Some("Disconnected".to_string());
+            }
+            Message::ConsoleEvent(event) => match event {
+                ConsoleEvent::Connected(writer) => {
+                    self.console_writer = Some(writer);
+                    self.console_status = self
+                        .console_port
+                        .as_ref()
+                        .map(|port| format!("Connected to {}", port.name));
+                }
+                ConsoleEvent::Data(bytes) => {
+                    let mut commands = vec![self.append_console_output(&bytes)];
+                    if let Some(parser) = &mut self.app_log_parser {
+                        let lines = parser.feed(&bytes);
+                        if !lines.is_empty() {
+                            commands.push(self.append_app_log(lines));
+                        }
+                    }
+                    return Command::batch(commands);
+                }
+                ConsoleEvent::Disconnected(reason) => {
+                    self.console_connect_requested = false;
+                    self.console_writer = None;
+                    if self.app_log_streaming {
+                        self.configuration_console_output
+                            .push_str(&format!("\nBadge output stopped: {}", reason));
+                    }
+                    self.stop_app_log();
+                    self.console_status = Some(reason);
+                }
+            },
+            Message::ConsoleInputChanged(input) => {
+                self.console_input = input;
+                self.console_history_index = None;
+            }
+            Message::ConsoleSend => {
+                if let Some(writer) = &self.console_writer {
+                    let line = std::mem::take(&mut self.console_input);
+                    if let Err(error) = writer.send(format!("{}\n", line).into_bytes()) {
+                        self.console_status = Some(error);
+                    }
+                    if !line.is_empty() && self.console_history.last() != Some(&line) {
+                        self.console_history.push(line);
+                    }
+                    self.console_history_index = None;
+                }
+            }
+            Message::ConsoleSendInterrupt => {
+                if let Some(writer) = &self.console_writer
+                    && let Err(error) = writer.send(vec![0x03])
+                {
+                    self.console_status = Some(error);
+                }
+            }
+            Message::ConsoleToggleHex(enabled) => {
+                self.console_hex_view = enabled;
+            }
+            Message::ConsoleClear => {
+                self.console_buffer.clear();
+            }
+            Message::ConsoleStartCapture => {
+                let dialog = rfd::AsyncFileDialog::new()
+                    .add_filter("Log file", &["log", "txt"])
+                    .set_file_name("badge_console.log");
+                return Command::perform(
+                    async move { dialog.save_file().await.map(|file| file.path().to_path_buf()) },
+                    Message::ConsoleCaptureTo,
+                );
+            }
+            Message::ConsoleCaptureTo(Some(path)) => match fs::File::create(&path) {
+                Ok(file) => {
+                    self.console_status = Some(format!("Capturing to {}", path.display()));
+                    self.console_capture = Some((path, file));
+                }
+                Err(e) => {
+                    self.console_status = Some(format!("Could not create {}: {}", path.display(), e));
+                }
+            },
+            Message::ConsoleCaptureTo(None) => {}
+            Message::ConsoleStopCapture => {
+                if let Some((path, _)) = self.console_capture.take() {
+                    self.console_status = Some(format!("Saved capture to {}", path.display()));
+                }
+            }
+            Message::AppLogTogglePause => {
+                self.app_log_paused_at = match self.app_log_paused_at {
+                    Some(_) => None,
+                    None => Some(self.app_log_lines.len()),
+                };
+                if self.app_log_paused_at.is_none() {
+                    return scrollable::snap_to(
+                        scrollable::Id::new(APP_LOG_SCROLL_ID),
+                        scrollable::RelativeOffset::END,
+                    );
+                }
+            }
+            Message::AppLogClear => {
+                self.app_log_lines.clear();
+                if self.app_log_paused_at.is_some() {
+                    self.app_log_paused_at = Some(0);
+                }
+            }
+            Message::AppLogStop => {
+                self.stop_app_log();
+            }
+            Message::RunEmulator => {
+                let remote_paths = &app_config::get().remote_paths;
+                let mut files = emulator::VirtualFiles::new();
+                files.insert(
+                    remote_paths.config.clone(),
+                    BadgeConfig::new(&self.badge_name, self.selected_led_mode)
+                        .to_file_contents()
+                        .into_bytes(),
+                );
+                files.insert(
+                    remote_paths.settings.clone(),
+                    self.device_settings
+                        .to_settings_txt(&self.badge_name)
+                        .into_bytes(),
+                );
+
+                self.emulator_output.clear();
+                match fs::read(self.selected_image_fwi_path()) {
+                    Ok(image_fwi) => {
+                        files.insert(remote_paths.image.clone(), image_fwi);
+                    }
+                    Err(e) => self.emulator_output.push(format!(
+                        "⚠ Could not read {}: {}",
+                        self.selected_image_fwi_path().display(),
+                        e
+                    )),
+                }
+
+                // Same check the Configure Device pipeline runs before uploading
+                if let Ok(bytes) = fs::read(&self.wasm_app().local_path) {
+                    let report = wasm_check::check_module(&self.wasm_app().file_name(), &bytes);
+                    self.emulator_output
+                        .extend(report.to_console_text().lines().map(str::to_string));
+                }
 
-                if filtered_name.len() <= 20 {
-                    self.badge_name = filtered_name;
+                self.emulator_files = Arc::new(files);
+                self.emulator_generation += 1;
+                self.emulator_running = true;
+                self.emulator_input = None;
+                self.emulator_screen = EmulatorScreen::default();
+                self.emulator_display_cache.clear();
+                self.emulator_started_at = Instant::now();
+                self.emulator_status = None;
+                if self.current_screen != AppScreen::Emulator {
+                    self.current_screen = AppScreen::Emulator;
                 }
             }
+            Message::EmulatorEvent(event) => match event {
+                EmulatorEvent::Started(input) => {
+                    self.emulator_input = Some(input);
+                }
+                EmulatorEvent::Screen(screen) => {
+                    self.emulator_screen = screen;
+                    self.emulator_display_cache.clear();
+                }
+                EmulatorEvent::Printed(line) => {
+                    self.emulator_output.push(line);
+                    let excess = self.emulator_output.len().saturating_sub(app_log::MAX_LINES);
+                    self.emulator_output.drain(..excess);
+                    return scrollable::snap_to(
+                        scrollable::Id::new(EMULATOR_SCROLL_ID),
+                        scrollable::RelativeOffset::END,
+                    );
+                }
+                EmulatorEvent::Exited(result) => {
+                    self.emulator_running = false;
+                    self.emulator_input = None;
+                    self.emulator_status = Some(result);
+                }
+            },
+            Message::EmulatorButton(badge_button) => {
+                if let Some(input) = &self.emulator_input {
+                    input.press(badge_button);
+                }
+            }
+            Message::EmulatorStop => {
+                self.emulator_running = false;
+                self.emulator_input = None;
+                self.emulator_status = Some(Ok("Stopped".to_string()));
+            }
+            Message::EmulatorTick(now) => {
+                self.emulator_led_elapsed = (now - self.emulator_started_at).as_secs_f32();
+            }
             Message::StartConfiguration => {
+                // fwi-serial needs the port; this run's output replaces the last one's
+                self.stop_app_log();
+                self.app_log_lines.clear();
                 self.is_configuring = true;
                 self.configuration_progress = 0.0;
                 self.configuration_status = "Starting configuration...".to_string();
                 self.configuration_error = None;
                 self.configuration_console_output = String::new();
+                self.run_log_status = None;
+                self.run_log_path = match run_log::start_run(&self.config_job()) {
+                    Ok(path) => Some(path),
+                    Err(error) => {
+                        println!("Run log: {}", error);
+                        None
+                    }
+                };
             }
             Message::ConfigurationStepUpdate(step_description, progress) => {
                 self.configuration_status = step_description.clone();
@@ -279,46 +1402,219 @@ impl Application for BuildABadgeApp {
             Message::ConfigurationComplete(result) => {
                 self.is_configuring = false;
                 self.configuration_progress = 1.0;
+                run_log::finish_run(&result);
 
                 match result {
                     Ok(message) => {
                         println!("Configuration successful: {}", message);
                         self.configuration_status = "Configuration successful!".to_string();
                         self.configuration_error = None;
+                        // The badge now holds this design; an old comparison would be misleading
+                        self.comparison_snapshot = None;
+                        self.configuration_diff = None;
                         // Keep the existing console output and append success message
                         if !self.configuration_console_output.is_empty() {
                             self.configuration_console_output.push_str("\n");
                         }
                         self.configuration_console_output.push_str(&message);
+                        self.start_app_log();
                     }
                     Err(error) => {
                         println!("Configuration failed: {}", error);
                         self.configuration_status = "Configuration failed".to_string();
-                        self.configuration_error = Some(error.clone());
                         // Keep the existing console output and append error message
                         if !self.configuration_console_output.is_empty() {
                             self.configuration_console_output.push_str("\n");
                         }
-                        self.configuration_console_output.push_str(&error);
+                        self.configuration_console_output.push_str(&format!(
+                            "✗ {}\nConfiguration stopped due to error.",
+                            error
+                        ));
+                        self.configuration_error = Some(error);
                     }
                 }
             }
-            Message::MouseButtonPressed(button) => {
-                // Handle mouse back and forward buttons
-                match button {
-                    mouse::Button::Back => {
-                        // Navigate backwards based on current screen
-                        let previous_screen = match self.current_screen {
-                            AppScreen::Welcome => None,
-                            AppScreen::CustomizeBadge => Some(AppScreen::Welcome),
-                            AppScreen::CustomizeLeds => Some(AppScreen::CustomizeBadge),
-                            AppScreen::NameBadge => Some(AppScreen::CustomizeLeds),
-                            AppScreen::Summary => Some(AppScreen::NameBadge),
-                        };
-                        
-                        if let Some(screen) = previous_screen {
-                            return self.update(Message::NavigateTo(screen));
-                        }
+            Message::AutosaveDesign => {
+                self.autosave_design();
+            }
+            Message::RecentDesignsLoaded(designs) => {
+                self.recent_designs = designs;
+            }
+            // Nothing to update; the redraw picks up the decoded artwork
+            Message::AssetsDecoded => {}
+            Message::OpenDesign(index) => {
+                if let Some(saved) = self.recent_designs.get(index).cloned() {
+                    self.open_design(&saved);
+                    let screen = if self.badge_name.is_empty() {
+                        AppScreen::CustomizeBadge
+                    } else {
+                        AppScreen::Summary
+                    };
+                    return Command::batch([
+                        self.render_picture(),
+                        self.update(Message::NavigateTo(screen)),
+                    ]);
+                }
+            }
+            Message::SelectThemePack(choice) => match choice.load() {
+                Ok(pack) => {
+                    // A gallery picture the new pack lacks stays selected, uploaded from
+                    // the old pack's .fwi
+                    if let Some(handle) = &self.selected_customize_image
+                        && pack.find(handle).is_none()
+                        && self.imported_image.is_none()
+                    {
+                        self.imported_image =
+                            theme_pack::active().find(handle).map(|gallery| ImportedImage {
+                                handle: handle.clone(),
+                                fwi_path: gallery.fwi_path.clone(),
+                                source_path: Some(gallery.source_path.clone()),
+                            });
+                    }
+                    theme_pack::set_active(pack);
+                    self.theme_pack_error = None;
+                    self.adjust_error = None;
+                    self.drop_missing_layers();
+
+                    // A design nobody has touched yet starts over with the pack's defaults
+                    if self.design_id.is_none() {
+                        self.selected_led_mode = default_led_mode();
+                        self.selected_customize_image = default_gallery_image();
+                        self.imported_image = None;
+                        self.adjustments = Adjustments::default();
+                        self.layers.clear();
+                        self.selected_layer = None;
+                        self.blank_design = self.current_design();
+                    }
+                    // Decorations with the same name may look different in this pack
+                    return Command::batch([
+                        self.render_picture(),
+                        Command::perform(
+                            assets::decode_pictures(theme_pack::active().pictures()),
+                            |()| Message::AssetsDecoded,
+                        ),
+                    ]);
+                }
+                Err(error) => {
+                    println!("Theme pack: {}", error);
+                    self.theme_pack_error = Some(error);
+                }
+            },
+            Message::NewDesign => {
+                self.autosave_design();
+                self.badge_name.clear();
+                self.selected_led_mode = default_led_mode();
+                self.selected_customize_image = default_gallery_image();
+                self.imported_image = None;
+                self.adjustments = Adjustments::default();
+                self.layers.clear();
+                self.selected_layer = None;
+                self.selected_wasm_app = 0;
+                self.device_settings = DeviceSettings::default();
+                self.refresh_wifi_qr_code();
+                self.design_id = None;
+                self.last_saved_design = None;
+                return self.update(Message::NavigateTo(AppScreen::CustomizeBadge));
+            }
+            Message::ExportBundle => {
+                let file_name = if self.badge_name.is_empty() {
+                    "design".to_string()
+                } else {
+                    self.badge_name.clone()
+                };
+                let dialog = rfd::AsyncFileDialog::new()
+                    .add_filter("Badge design", &[bundle::EXTENSION])
+                    .set_file_name(format!("{}.{}", file_name, bundle::EXTENSION));
+                return Command::perform(
+                    async move { dialog.save_file().await.map(|file| file.path().to_path_buf()) },
+                    Message::ExportBundleTo,
+                );
+            }
+            Message::ExportBundleTo(Some(path)) => {
+                self.backup_status = None;
+                let source_path = self.picture_source_path();
+                let source_image = source_path
+                    .extension()
+                    .is_none_or(|extension| !extension.eq_ignore_ascii_case("fwi"))
+                    .then_some(source_path.as_path());
+                let design = self.current_design();
+                let picture_path = design.is_edited().then(|| self.picture_fwi_path());
+                self.bundle_status = Some(
+                    bundle::export(
+                        &path,
+                        &design,
+                        &self.selected_image_fwi_path(),
+                        picture_path.as_deref(),
+                        source_image,
+                        self.wasm_app(),
+                    )
+                    .map(|()| format!("Design exported to {}", path.display())),
+                );
+            }
+            Message::ExportBundleTo(None) => {}
+            Message::ImportBundle => {
+                let dialog = rfd::AsyncFileDialog::new()
+                    .add_filter("Badge design", &[bundle::EXTENSION]);
+                return Command::perform(
+                    async move { dialog.pick_file().await.map(|file| file.path().to_path_buf()) },
+                    Message::ImportBundleFrom,
+                );
+            }
+            Message::ImportBundleFrom(Some(path)) => match bundle::import(&path) {
+                Ok(saved) => {
+                    self.autosave_design();
+                    self.backup_status = None;
+                    self.bundle_status =
+                        Some(Ok(format!("Imported {}", path.display())));
+                    self.recent_designs.insert(0, saved);
+                    return self.update(Message::OpenDesign(0));
+                }
+                Err(error) => self.bundle_status = Some(Err(error)),
+            },
+            Message::ImportBundleFrom(None) => {}
+            Message::SaveRunLog => {
+                let dialog = rfd::AsyncFileDialog::new()
+                    .add_filter("JSON lines", &["jsonl"])
+                    .set_file_name("build_a_badge_run.jsonl");
+                return Command::perform(
+                    async move { dialog.save_file().await.map(|file| file.path().to_path_buf()) },
+                    Message::SaveRunLogTo,
+                );
+            }
+            Message::SaveRunLogTo(Some(path)) => {
+                if let Some(run_log_path) = &self.run_log_path {
+                    self.run_log_status = Some(
+                        fs::copy(run_log_path, &path)
+                            .map(|_| format!("Log saved to {}", path.display()))
+                            .map_err(|e| format!("Could not save {}: {}", path.display(), e)),
+                    );
+                }
+            }
+            Message::SaveRunLogTo(None) => {}
+            Message::OpenLogsFolder => {
+                self.run_log_status = run_log::open_logs_folder().err().map(Err);
+            }
+            Message::MouseButtonPressed(button) => {
+                // Handle mouse back and forward buttons
+                match button {
+                    mouse::Button::Back => {
+                        // Navigate backwards based on current screen
+                        let previous_screen = match self.current_screen {
+                            AppScreen::Welcome => None,
+                            AppScreen::CustomizeBadge => Some(AppScreen::Welcome),
+                            AppScreen::CustomizeLeds => Some(AppScreen::CustomizeBadge),
+                            AppScreen::NameBadge => Some(AppScreen::CustomizeLeds),
+                            AppScreen::ChooseApp => Some(AppScreen::NameBadge),
+                            AppScreen::Summary => Some(AppScreen::ChooseApp),
+                            AppScreen::AdvancedSettings => Some(AppScreen::Summary),
+                            AppScreen::Emulator => Some(AppScreen::Summary),
+                            AppScreen::FileBrowser => Some(AppScreen::Welcome),
+                            AppScreen::Console => Some(self.console_return_screen),
+                        };
+                        
+                        if let Some(screen) = previous_screen {
+                            return self.update(Message::NavigateTo(screen));
+                        }
                     }
                     mouse::Button::Forward => {
                         // Navigate forwards based on current screen
@@ -326,8 +1622,13 @@ impl Application for BuildABadgeApp {
                             AppScreen::Welcome => Some(AppScreen::CustomizeBadge),
                             AppScreen::CustomizeBadge => Some(AppScreen::CustomizeLeds),
                             AppScreen::CustomizeLeds => Some(AppScreen::NameBadge),
-                            AppScreen::NameBadge => Some(AppScreen::Summary),
+                            AppScreen::NameBadge => Some(AppScreen::ChooseApp),
+                            AppScreen::ChooseApp => Some(AppScreen::Summary),
                             AppScreen::Summary => None,
+                            AppScreen::AdvancedSettings => None,
+                            AppScreen::FileBrowser => None,
+                            AppScreen::Console => None,
+                            AppScreen::Emulator => None,
                         };
                         
                         if let Some(screen) = next_screen {
@@ -337,6 +1638,25 @@ impl Application for BuildABadgeApp {
                     _ => {} // Ignore other mouse buttons
                 }
             }
+            Message::KeyPressed(iced::keyboard::Key::Named(
+                named @ (iced::keyboard::key::Named::ArrowUp | iced::keyboard::key::Named::ArrowDown),
+            )) if self.current_screen == AppScreen::Console => {
+                // Shell-style history recall in the console input
+                if self.console_history.is_empty() {
+                    return Command::none();
+                }
+                let last = self.console_history.len() - 1;
+                self.console_history_index = match (named, self.console_history_index) {
+                    (iced::keyboard::key::Named::ArrowUp, None) => Some(last),
+                    (iced::keyboard::key::Named::ArrowUp, Some(index)) => Some(index.saturating_sub(1)),
+                    (_, Some(index)) if index < last => Some(index + 1),
+                    _ => None,
+                };
+                self.console_input = self
+                    .console_history_index
+                    .map(|index| self.console_history[index].clone())
+                    .unwrap_or_default();
+            }
             Message::KeyPressed(key) => {
                 // Handle Enter key on welcome screen to trigger start button
                 if let iced::keyboard::Key::Named(iced::keyboard::key::Named::Enter) = key {
@@ -354,14 +1674,31 @@ impl Application for BuildABadgeApp {
         
         // Add mouse button subscription for navigation
         subscriptions.push(
-            event::listen_with(|event, _status| {
+            event::listen_with(|event, status| {
                 match event {
                     event::Event::Mouse(mouse::Event::ButtonPressed(button)) => {
                         Some(Message::MouseButtonPressed(button))
                     }
+                    // Left alone when a text field is focused, so Ctrl+V still pastes text there
+                    event::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. })
+                        if modifiers.command()
+                            && key.as_ref() == keyboard::Key::Character("v")
+                            && status == event::Status::Ignored =>
+                    {
+                        Some(Message::PasteImage)
+                    }
                     event::Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) => {
                         Some(Message::KeyPressed(key))
                     }
+                    event::Event::Window(_, window::Event::FileHovered(_)) => {
+                        Some(Message::FileHovered(true))
+                    }
+                    event::Event::Window(_, window::Event::FilesHoveredLeft) => {
+                        Some(Message::FileHovered(false))
+                    }
+                    event::Event::Window(_, window::Event::FileDropped(path)) => {
+                        Some(Message::FileDropped(path))
+                    }
                     _ => None,
                 }
             })
@@ -369,13 +1706,42 @@ impl Application for BuildABadgeApp {
         
         // Add configuration subscription if configuring
         if self.is_configuring {
-            let config_subscription = configuration_subscription(
-                self.selected_customize_image.clone(),
-                self.selected_led_mode,
-                self.badge_name.clone(),
-            );
+            let config_subscription = configuration_subscription(self.config_job());
             subscriptions.push(config_subscription);
         }
+
+        // fwi-serial needs the port to itself, so the connection is dropped while it
+        // runs and reopened after. The console and the badge output panel share it.
+        if let Some(port) = self
+            .console_port
+            .as_ref()
+            .filter(|_| self.console_connect_requested || self.app_log_streaming)
+            .filter(|_| !self.is_device_busy())
+        {
+            subscriptions.push(
+                serial_console::connect(port.name.clone(), serial_console::DEFAULT_BAUD_RATE)
+                    .map(Message::ConsoleEvent),
+            );
+        }
+        
+        if self.emulator_running && self.current_screen == AppScreen::Emulator {
+            subscriptions.push(
+                emulator::run(
+                    self.wasm_app().local_path.clone(),
+                    self.emulator_files.clone(),
+                    self.emulator_generation,
+                )
+                .map(Message::EmulatorEvent),
+            );
+            if self.emulator_screen.led_mode.is_some() {
+                subscriptions.push(
+                    iced::time::every(Duration::from_millis(66)).map(Message::EmulatorTick),
+                );
+            }
+        }
+
+        // Checked often so a crash loses at most a couple of seconds of work
+        subscriptions.push(iced::time::every(AUTOSAVE_INTERVAL).map(|_| Message::AutosaveDesign));
         
         Subscription::batch(subscriptions)
     }
@@ -386,7 +1752,12 @@ impl Application for BuildABadgeApp {
             AppScreen::CustomizeBadge => self.render_customize_badge_screen(),
             AppScreen::CustomizeLeds => self.render_customize_leds_screen(),
             AppScreen::NameBadge => self.render_name_badge_screen(),
+            AppScreen::ChooseApp => self.render_choose_app_screen(),
             AppScreen::Summary => self.render_summary_screen(),
+            AppScreen::AdvancedSettings => self.render_advanced_settings_screen(),
+            AppScreen::FileBrowser => self.render_file_browser_screen(),
+            AppScreen::Console => self.render_console_screen(),
+            AppScreen::Emulator => self.render_emulator_screen(),
         };
 
         // No transitions - just show the current screen directly
@@ -401,6 +1772,421 @@ impl Application for BuildABadgeApp {
 
 // --- BuildABadgeApp Implementation - Custom Methods (Rendering and Helpers) ---
 impl BuildABadgeApp {
+    /// Secured access points always need a passphrase; make one up if the user hasn't.
+    fn ensure_wifi_passphrase(&mut self) {
+        if self.device_settings.wifi_ap_auth && self.device_settings.wifi_ap_passphrase.is_empty() {
+            self.device_settings.wifi_ap_passphrase = settings::generate_passphrase();
+        }
+        self.refresh_wifi_qr_code();
+    }
+
+    /// Pre-fills the wizard from files read off a badge. Whatever can't be parsed
+    /// keeps its current value.
+    fn apply_device_snapshot(&mut self, snapshot: &DeviceSnapshot) {
+        if let Some(config_text) = &snapshot.config_text {
+            match BadgeConfig::parse(config_text) {
+                Ok(config) => {
+                    self.badge_name = config.name;
+                    if config.led_mode.is_some() {
+                        self.selected_led_mode = config.led_mode;
+                    }
+                }
+                Err(error) => println!("Read from badge: {}", error),
+            }
+        }
+
+        if let Some(settings_text) = &snapshot.settings_text {
+            self.device_settings = DeviceSettings::parse(settings_text, &self.badge_name);
+            if let Some(index) = self
+                .wasm_apps
+                .iter()
+                .position(|app| app.remote_path() == self.device_settings.default_script)
+            {
+                self.selected_wasm_app = index;
+            }
+        }
+
+        self.imported_image = None;
+        // What's on the badge already has any edits in it
+        self.adjustments = Adjustments::default();
+        self.layers.clear();
+        self.selected_layer = None;
+        // The read-back folder is reused, so the next autosave must copy the picture again
+        self.last_saved_design = None;
+        if let Some(image_fwi) = &snapshot.image_fwi {
+            match fwi::FwiImage::decode(image_fwi) {
+                Ok(decoded) => {
+                    let handle = decoded.to_handle();
+                    self.selected_customize_image = Some(handle.clone());
+                    self.imported_image = Some(ImportedImage {
+                        handle,
+                        fwi_path: snapshot.image_path.clone(),
+                        source_path: None,
+                    });
+                }
+                Err(error) => println!("Read from badge: {}", error),
+            }
+        }
+
+        self.refresh_wifi_qr_code();
+    }
+
+    fn wasm_app(&self) -> &WasmApp {
+        &self.wasm_apps[self.selected_wasm_app]
+    }
+
+    fn current_design(&self) -> Design {
+        Design {
+            badge_name: self.badge_name.clone(),
+            led_mode: self.selected_led_mode.map(|mode| mode.as_integer()),
+            gallery_image: self
+                .selected_customize_image
+                .as_ref()
+                .and_then(gallery_name),
+            source_image: self
+                .selected_imported_image()
+                .and_then(|imported| imported.source_path.clone()),
+            wasm_app: self.wasm_app().local_path.clone(),
+            settings_txt: self.device_settings.to_settings_txt(&self.badge_name),
+            adjustments: self.adjustments,
+            layers: self.layers.clone(),
+        }
+    }
+
+    /// Saves the design if it changed since the last save. Untouched defaults are
+    /// not worth a place in the recent designs list.
+    fn autosave_design(&mut self) {
+        let design = self.current_design();
+        let image_path = self.selected_image_fwi_path();
+        if self.design_id.is_none() && design == self.blank_design {
+            return;
+        }
+        if let Some((saved, saved_image_path)) = &self.last_saved_design
+            && *saved == design
+            && *saved_image_path == image_path
+        {
+            return;
+        }
+
+        let copy_image = self
+            .last_saved_design
+            .as_ref()
+            .is_none_or(|(_, saved_image_path)| *saved_image_path != image_path);
+        let picture_path = design.is_edited().then(|| self.picture_fwi_path());
+        let id = self.design_id.get_or_insert_with(design::new_id).clone();
+        if let Err(error) =
+            design::save(&id, &design, &image_path, picture_path.as_deref(), copy_image)
+        {
+            println!("Designs: {}", error);
+        }
+        // Marked saved even on failure, so a full disk isn't retried every tick
+        self.last_saved_design = Some((design, image_path));
+    }
+
+    /// Puts a saved design back into the wizard.
+    fn open_design(&mut self, saved: &SavedDesign) {
+        let design = &saved.design;
+        self.badge_name = design.badge_name.clone();
+        self.selected_led_mode = design.led_mode.and_then(LedMode::from_integer);
+
+        self.imported_image = None;
+        self.adjustments = design.adjustments;
+        self.layers = design.layers.clone();
+        self.selected_layer = None;
+        self.selected_customize_image = match design.gallery_image.as_deref().and_then(gallery_image) {
+            Some(handle) => Some(handle),
+            None => {
+                // Without the picture under them, the edits live only in image.fwi,
+                // so they start over from it
+                let picture_path = saved.picture_path().unwrap_or_else(|| {
+                    self.adjustments = Adjustments::default();
+                    self.layers.clear();
+                    saved.image_path()
+                });
+                let fwi_path = opened_design_image(saved, &picture_path);
+                match fs::read(&fwi_path)
+                    .map_err(|e| format!("Could not read {}: {}", fwi_path.display(), e))
+                    .and_then(|bytes| fwi::FwiImage::decode(&bytes))
+                {
+                    Ok(decoded) => {
+                        let handle = decoded.to_handle();
+                        self.imported_image = Some(ImportedImage {
+                            handle: handle.clone(),
+                            fwi_path,
+                            source_path: design.source_image.clone(),
+                        });
+                        Some(handle)
+                    }
+                    Err(error) => {
+                        println!("Designs: {}", error);
+                        None
+                    }
+                }
+            }
+        };
+        self.drop_missing_layers();
+
+        match self
+            .wasm_apps
+            .iter()
+            .position(|app| app.local_path == design.wasm_app)
+        {
+            Some(index) => self.selected_wasm_app = index,
+            None => match WasmApp::from_file(&design.wasm_app) {
+                Ok(app) => {
+                    self.wasm_apps.push(app);
+                    self.selected_wasm_app = self.wasm_apps.len() - 1;
+                }
+                Err(error) => println!("Designs: {}", error),
+            },
+        }
+
+        self.device_settings = DeviceSettings::parse(&design.settings_txt, &self.badge_name);
+        self.refresh_wifi_qr_code();
+
+        self.design_id = Some(saved.id.clone());
+        self.last_saved_design = Some((self.current_design(), self.selected_image_fwi_path()));
+    }
+
+    fn config_job(&self) -> ConfigJob {
+        ConfigJob {
+            image_fwi_path: self.selected_image_fwi_path(),
+            led_mode: self.selected_led_mode,
+            badge_name: self.badge_name.clone(),
+            device_settings: self.device_settings.clone(),
+            wasm_app: self.wasm_app().clone(),
+        }
+    }
+
+    /// Drops layers whose decoration isn't in the active theme pack, as after
+    /// switching packs or opening a design made with another.
+    fn drop_missing_layers(&mut self) {
+        let pack = theme_pack::active();
+        let count = self.layers.len();
+        self.layers.retain(|layer| {
+            let is_present = pack.find_decoration(layer.kind, &layer.name).is_some();
+            if !is_present {
+                println!("Layers: Dropping \"{}\", which is not in the {} theme", layer.name, pack.name);
+            }
+            is_present
+        });
+        if self.layers.len() != count {
+            self.selected_layer = None;
+        }
+    }
+
+    /// The `.fwi` file uploaded for the currently selected picture.
+    fn selected_image_fwi_path(&self) -> PathBuf {
+        match self.adjusted_picture() {
+            Some(picture) => picture.fwi_path.clone(),
+            None => self.picture_fwi_path(),
+        }
+    }
+
+    /// The selected picture's `.fwi` before adjustments and layers.
+    fn picture_fwi_path(&self) -> PathBuf {
+        // An imported picture is re-uploaded from the .fwi it came from
+        if let Some(imported) = self.selected_imported_image() {
+            return imported.fwi_path.clone();
+        }
+
+        // Map the image handle to the corresponding .fwi file
+        self.selected_customize_image
+            .as_ref()
+            .and_then(|handle| theme_pack::active().find(handle).map(|gallery| gallery.fwi_path.clone()))
+            .unwrap_or_else(|| PathBuf::from("assets/badge_placeholder.fwi"))
+    }
+
+    fn selected_imported_image(&self) -> Option<&ImportedImage> {
+        self.imported_image
+            .as_ref()
+            .filter(|imported| self.selected_customize_image.as_ref() == Some(&imported.handle))
+    }
+
+    /// The original of the selected picture if it's known, else its `.fwi`.
+    /// Adjusting the original avoids quantizing the colors twice.
+    fn picture_source_path(&self) -> PathBuf {
+        let source_path = match self.selected_imported_image() {
+            Some(imported) => imported.source_path.clone(),
+            None => self
+                .selected_customize_image
+                .as_ref()
+                .and_then(|handle| {
+                    theme_pack::active()
+                        .find(handle)
+                        .map(|gallery| gallery.source_path.clone())
+                }),
+        };
+        source_path
+            .filter(|path| path.exists())
+            .unwrap_or_else(|| self.picture_fwi_path())
+    }
+
+    /// Whether the selected picture has slider changes or layers to render.
+    fn is_picture_edited(&self) -> bool {
+        self.selected_customize_image.is_some()
+            && (!self.adjustments.is_identity() || !self.layers.is_empty())
+    }
+
+    /// The selected picture with the current sliders and layers applied, once
+    /// `render_picture` has made it.
+    fn adjusted_picture(&self) -> Option<&AdjustedPicture> {
+        if !self.is_picture_edited() {
+            return None;
+        }
+        let source_path = self.picture_source_path();
+        self.adjusted_picture.as_ref().filter(|picture| {
+            picture.is_for(&source_path, &self.adjustments, &self.layers, &theme_pack::active())
+        })
+    }
+
+    /// Whether the selected picture is edited but not rendered yet, so uploading
+    /// now would send it without its edits.
+    fn is_picture_pending(&self) -> bool {
+        if !self.is_picture_edited() || self.adjusted_picture().is_some() {
+            return false;
+        }
+        let source_path = self.picture_source_path();
+        !self
+            .adjust_error
+            .as_ref()
+            .is_some_and(|(failed_path, failed_adjustments, failed_layers, _)| {
+                *failed_path == source_path
+                    && *failed_adjustments == self.adjustments
+                    && *failed_layers == self.layers
+            })
+    }
+
+    /// Renders the selected picture with the sliders and layers applied on a
+    /// blocking thread, unless that's done, has failed or isn't needed. One render
+    /// runs at a time; `PictureRendered` starts the next if anything changed
+    /// meanwhile. Called after anything that changes the picture, sliders, layers
+    /// or theme pack.
+    fn render_picture(&mut self) -> Command<Message> {
+        if self.is_rendering_picture || !self.is_picture_pending() {
+            return Command::none();
+        }
+        self.is_rendering_picture = true;
+        Command::perform(
+            adjust::render(
+                self.adjusted_picture.clone(),
+                self.picture_source_path(),
+                self.adjustments,
+                self.layers.clone(),
+                theme_pack::active(),
+            ),
+            Message::PictureRendered,
+        )
+    }
+
+    fn refresh_configuration_diff(&mut self) {
+        self.configuration_diff = self.comparison_snapshot.as_ref().map(|snapshot| {
+            let local_image = fs::read(self.selected_image_fwi_path()).ok();
+            diff::diff_configuration(
+                &BadgeConfig::new(&self.badge_name, self.selected_led_mode),
+                &self.device_settings.to_settings_txt(&self.badge_name),
+                local_image.as_deref(),
+                snapshot,
+            )
+        });
+    }
+
+    /// Adds serial output to the scrollback and capture file, keeping the view pinned to the end.
+    fn append_console_output(&mut self, bytes: &[u8]) -> Command<Message> {
+        if let Some((path, file)) = &mut self.console_capture {
+            use std::io::Write;
+            if let Err(e) = file.write_all(bytes) {
+                self.console_status = Some(format!("Capture to {} stopped: {}", path.display(), e));
+                self.console_capture = None;
+            }
+        }
+
+        self.console_buffer.extend_from_slice(bytes);
+        let excess = self
+            .console_buffer
+            .len()
+            .saturating_sub(serial_console::SCROLLBACK_BYTES);
+        self.console_buffer.drain(..excess);
+
+        scrollable::snap_to(
+            scrollable::Id::new(CONSOLE_SCROLL_ID),
+            scrollable::RelativeOffset::END,
+        )
+    }
+
+    /// Whether an `fwi-serial` command is running, which needs the badge's serial
+    /// port to itself.
+    fn is_device_busy(&self) -> bool {
+        self.is_configuring
+            || self.is_reading_device
+            || self.is_comparing_device
+            || self.is_backing_up
+            || self.is_browser_busy
+    }
+
+    /// Keeps the badge's serial port open after a successful run so whatever its
+    /// app prints shows up on the Summary screen. The port is let go while any
+    /// other badge action runs (see `is_device_busy`) and picked up again after.
+    fn start_app_log(&mut self) {
+        // Only a port the badge enumerates as; anything else may be another device
+        if !self.console_port.as_ref().is_some_and(|port| port.is_badge) {
+            self.console_ports = serial_console::available_ports();
+            if let Some(port) = self.console_ports.iter().find(|port| port.is_badge) {
+                self.console_port = Some(port.clone());
+            }
+        }
+
+        match self.console_port.as_ref().filter(|port| port.is_badge) {
+            Some(port) => {
+                println!("App log: Streaming badge output from {}", port.name);
+                self.configuration_console_output
+                    .push_str(&format!("\nListening for badge output on {}...", port.name));
+                self.app_log_streaming = true;
+                self.app_log_parser = Some(LogParser::new());
+                self.app_log_paused_at = None;
+            }
+            None => {
+                self.configuration_console_output
+                    .push_str("\nNo badge serial port found, badge output won't be shown");
+            }
+        }
+    }
+
+    fn stop_app_log(&mut self) {
+        if self.app_log_streaming && !self.console_connect_requested {
+            self.console_writer = None;
+        }
+        self.app_log_streaming = false;
+        self.app_log_parser = None;
+        self.app_log_paused_at = None;
+    }
+
+    fn append_app_log(&mut self, lines: Vec<LogLine>) -> Command<Message> {
+        self.app_log_lines.extend(lines);
+        let excess = self.app_log_lines.len().saturating_sub(app_log::MAX_LINES);
+        self.app_log_lines.drain(..excess);
+        if let Some(paused_at) = &mut self.app_log_paused_at {
+            *paused_at = paused_at.saturating_sub(excess);
+            return Command::none();
+        }
+
+        scrollable::snap_to(
+            scrollable::Id::new(APP_LOG_SCROLL_ID),
+            scrollable::RelativeOffset::END,
+        )
+    }
+
+    fn refresh_wifi_qr_code(&mut self) {
+        self.wifi_qr_code = if self.device_settings.is_wifi_secured() {
+            qr::render_qr(&qr::wifi_join_payload(
+                &self.device_settings.effective_ssid(&self.badge_name),
+                &self.device_settings.wifi_ap_passphrase,
+            ))
+        } else {
+            None
+        };
+    }
+
     fn render_welcome_screen(&self) -> Element<Message> {
         let start_button = button(
             text("Start")
@@ -411,9 +2197,84 @@ impl BuildABadgeApp {
         .padding([10, 40])
         .style(theme_fn(YellowButtonStyle));
 
+        let read_badge_button = button(
+            text(if self.is_reading_device {
+                "Reading..."
+            } else {
+                "Read from badge"
+            })
+            .size(BODY_SIZE),
+        )
+        .on_press_maybe(if self.is_reading_device {
+            None
+        } else {
+            Some(Message::ReadFromBadge)
+        })
+        .padding([8, 20])
+        .style(if self.is_reading_device {
+            theme_fn(DisabledButtonStyle)
+        } else {
+            theme_fn(YellowButtonStyle)
+        });
+
+        let read_badge_status = match (&self.device_read_error, &self.bundle_status) {
+            (Some(error), _) | (None, Some(Err(error))) => text(error)
+                .size(14)
+                .horizontal_alignment(iced::alignment::Horizontal::Center)
+                .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+            (None, _) => text(""),
+        };
+
+        // A broken config.toml or theme pack falls back to defaults, which should not go unnoticed
+        let config_warnings = app_config::get()
+            .warnings
+            .iter()
+            .chain(&self.theme_pack_error)
+            .cloned()
+            .collect::<Vec<_>>();
+        let config_warnings = text(config_warnings.join("\n"))
+            .size(14)
+            .horizontal_alignment(iced::alignment::Horizontal::Center)
+            .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0)));
+
+        let pack = theme_pack::active();
+        let welcome_text = pack
+            .welcome_text
+            .iter()
+            .fold(column![].spacing(10), |paragraphs, paragraph| {
+                paragraphs.push(
+                    container::<_, Theme, iced::Renderer>(
+                        text(paragraph)
+                            .width(Length::Fill)
+                            .horizontal_alignment(iced::alignment::Horizontal::Center)
+                            .size(BODY_SIZE),
+                    )
+                    .padding([0, 80]), // Reduced horizontal padding from 100 to 80
+                )
+            });
+
+        // Only worth a picker once an event has installed a pack of its own
+        let theme_picker = (self.theme_packs.len() > 1).then(|| {
+            row![
+                text("Event theme:").size(14),
+                pick_list(
+                    self.theme_packs.clone(),
+                    Some(PackChoice::of(&pack)),
+                    Message::SelectThemePack,
+                )
+                .text_size(14),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        });
+
         // Create a container for the logo with a smaller, responsive height
         let app_logo_container = container(
-            image(APP_LOGO_IMAGE.clone())
+            image(
+                pack.logo
+                    .as_deref()
+                    .map_or_else(|| assets::handle(Asset::Logo), assets::picture),
+            )
                 .width(Length::Fixed(300.0)) // Reduced from 400 to 300
                 .height(Length::Fixed(250.0)) // Reduced from 400 to 250
                 .content_fit(ContentFit::ScaleDown), // Ensure the image scales down
@@ -427,24 +2288,31 @@ impl BuildABadgeApp {
         Space::new(Length::Shrink, Length::Fixed(20.0)), // Reduced from 50
         app_logo_container,
         Space::new(Length::Shrink, Length::Fixed(15.0)), // Reduced from 20
-        container::<_, Theme, iced::Renderer>(
-            text("Welcome to the Build-A-Badge Workshop, where creativity and fun come alive for all! Dive into a vibrant space that sparks your imagination, encourages discovery, and empowers you to craft a unique DEFCON badge—along with memories to cherish forever.")
-            .width(Length::Fill)
-            .horizontal_alignment(iced::alignment::Horizontal::Center)
-            .size(BODY_SIZE)
-        )
-        .padding([0, 80]), // Reduced horizontal padding from 100 to 80
-        Space::new(Length::Shrink, Length::Fixed(10.0)), // Reduced from 20
-        container::<_, Theme, iced::Renderer>(
-            text("What's in store? Get ready to hack your ICS Village Badge with a hands-on, interactive experience! You'll create custom script and design your very own application, guided every step of the way by a friendly ICS Village Badge Builder associate.")
-            .width(Length::Fill)
-            .horizontal_alignment(iced::alignment::Horizontal::Center)
-            .size(BODY_SIZE)
-        )
-        .padding([0, 80]), // Reduced horizontal padding from 100 to 80
+        welcome_text,
         Space::new(Length::Shrink, Length::Fixed(20.0)), // Reduced from 40
-        start_button
+        start_button,
+        row![
+            read_badge_button,
+            button(text("Badge files").size(BODY_SIZE))
+                .on_press(Message::NavigateTo(AppScreen::FileBrowser))
+                .padding([8, 20])
+                .style(theme_fn(YellowButtonStyle)),
+            button(text("Serial console").size(BODY_SIZE))
+                .on_press(Message::NavigateTo(AppScreen::Console))
+                .padding([8, 20])
+                .style(theme_fn(YellowButtonStyle)),
+            button(text("Import design...").size(BODY_SIZE))
+                .on_press(Message::ImportBundle)
+                .padding([8, 20])
+                .style(theme_fn(YellowButtonStyle)),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center),
+        read_badge_status,
+        self.render_recent_designs(),
+        config_warnings
     ]
+    .push_maybe(theme_picker)
     .spacing(15) // Reduced from 20
     .align_items(Alignment::Center)
     .width(Length::Fill)
@@ -452,11 +2320,96 @@ impl BuildABadgeApp {
     .into()
     }
 
+    /// "Resume last design" and thumbnails of earlier designs, for the Welcome screen.
+    fn render_recent_designs(&self) -> Element<'_, Message> {
+        if self.recent_designs.is_empty() {
+            return Space::new(Length::Shrink, Length::Shrink).into();
+        }
+
+        let new_design_button = button(text("New design").size(BODY_SIZE))
+            .on_press(Message::NewDesign)
+            .padding([8, 20])
+            .style(theme_fn(YellowButtonStyle));
+        // The design being edited is the newest, so there's nothing to resume
+        let resume_button = (self.design_id.as_ref() != Some(&self.recent_designs[0].id)).then(|| {
+            button(text("Resume last design").size(BODY_SIZE))
+                .on_press(Message::OpenDesign(0))
+                .padding([8, 20])
+                .style(theme_fn(YellowButtonStyle))
+        });
+
+        let cards = self
+            .recent_designs
+            .iter()
+            .take(RECENT_DESIGNS_SHOWN)
+            .enumerate()
+            .fold(row![].spacing(10), |cards, (index, saved)| {
+                let thumbnail: Element<Message> = match &saved.thumbnail {
+                    Some(handle) => image(handle.clone())
+                        .width(Length::Fixed(96.0))
+                        .height(Length::Fixed(72.0))
+                        .content_fit(ContentFit::Contain)
+                        .into(),
+                    None => Space::new(Length::Fixed(96.0), Length::Fixed(72.0)).into(),
+                };
+                let name = if saved.design.badge_name.is_empty() {
+                    "(no name)"
+                } else {
+                    &saved.design.badge_name
+                };
+                let is_current = self.design_id.as_ref() == Some(&saved.id);
+
+                cards.push(
+                    button(
+                        column![
+                            thumbnail,
+                            text(name).size(14),
+                            text(saved.age_text())
+                                .size(12)
+                                .style(iced::theme::Text::Color(Color::from_rgb8(100, 100, 100))),
+                        ]
+                        .spacing(2)
+                        .align_items(Alignment::Center),
+                    )
+                    .on_press(Message::OpenDesign(index))
+                    .padding(5)
+                    .style(if is_current {
+                        theme_fn(SelectedBadgeStyle)
+                    } else {
+                        theme_fn(DefaultBadgeStyle)
+                    }),
+                )
+            });
+
+        column![
+            row![new_design_button]
+                .push_maybe(resume_button)
+                .spacing(10),
+            text("Recent designs")
+                .size(14)
+                .style(iced::theme::Text::Color(text_color())),
+            cards,
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
     fn render_customize_badge_screen(&self) -> Element<Message> {
-        let display_image_handle = self
-            .selected_customize_image
+        // The last render stays up while the next one is made, so dragging a slider
+        // doesn't flash the unedited picture
+        let source_path = self.picture_source_path();
+        let last_render = self
+            .adjusted_picture
             .as_ref()
-            .map_or_else(|| BADGE_PLACEHOLDER_IMAGE.clone(), |h| h.clone());
+            .filter(|picture| self.is_picture_edited() && picture.is_of(&source_path));
+        let display_image_handle = match last_render {
+            Some(picture) => picture.preview.clone(),
+            None => self
+                .selected_customize_image
+                .as_ref()
+                .map_or_else(|| assets::handle(Asset::BadgePlaceholder), shown_picture),
+        };
 
         let user_image_widget = image(display_image_handle.clone())
             .width(Length::Fill)
@@ -464,30 +2417,59 @@ impl BuildABadgeApp {
             .content_fit(ContentFit::ScaleDown);
 
         let user_image_container = container(user_image_widget)
-            .width(Length::FillPortion(2))
+            .width(Length::Fill)
             .height(Length::FillPortion(2))
             .center_x()
             .center_y()
             .style(theme_fn_container(UserImageBorderStyle));
+        let preview_column = column![user_image_container]
+            .push_maybe(
+                self.selected_customize_image
+                    .is_some()
+                    .then(|| self.render_adjustment_controls()),
+            )
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .width(Length::FillPortion(2));
 
         let choose_image_text = text("Choose a picture for your badge:")
             .size(BODY_SIZE)
             .horizontal_alignment(iced::alignment::Horizontal::Center)
             .width(Length::Fill);
 
-        let images_to_select = [&DEFCON_LOGO_IMAGE, &DOGE_IMAGE, &PUPPY_IMAGE, &PIP_BOY_IMAGE, &VEGAS_IMAGE];
+        let paste_shortcut = if cfg!(target_os = "macos") {
+            "Cmd+V"
+        } else {
+            "Ctrl+V"
+        };
+        let picture_status = match &self.picture_error {
+            _ if self.is_file_hovering => text("Drop to use this picture")
+                .size(14)
+                .style(iced::theme::Text::Color(text_color())),
+            _ if self.converting_picture.is_some() => text("Preparing picture...").size(14),
+            Some(error) => text(error)
+                .size(14)
+                .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+            None => text(format!(
+                "or drop one onto the window, or paste one with {}",
+                paste_shortcut
+            ))
+            .size(14),
+        };
+
+        let pack = theme_pack::active();
+        let images_to_select = pack.gallery.iter().map(|gallery| &gallery.handle);
 
         let mut image_selection_row = row![]
             .spacing(10)
             .align_items(Alignment::Center)
             .width(Length::Fill);
 
-        for img_handle_lazy in images_to_select.iter() {
-            let img_handle_to_compare = (**img_handle_lazy).clone();
+        for img_handle in images_to_select {
             let is_selected = self
                 .selected_customize_image
                 .as_ref()
-                .map_or(false, |selected| selected.eq(&img_handle_to_compare));
+                .map_or(false, |selected| selected.eq(img_handle));
 
             let button_style = if is_selected {
                 theme_fn(SelectedBadgeStyle)
@@ -496,14 +2478,14 @@ impl BuildABadgeApp {
             };
 
             let image_button_content: iced::widget::Image<image::Handle> =
-                image((**img_handle_lazy).clone())
+                image(shown_picture(img_handle))
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .content_fit(ContentFit::ScaleDown);
 
             let image_button: iced::widget::Button<'_, Message, Theme, iced::Renderer> =
                 button(image_button_content)
-                    .on_press(Message::SelectCustomizeImage((**img_handle_lazy).clone()))
+                    .on_press(Message::SelectCustomizeImage(img_handle.clone()))
                     .padding(5)
                     .style(button_style);
 
@@ -530,7 +2512,7 @@ impl BuildABadgeApp {
         let finish_button_message = if finish_button_enabled {
             Message::NavigateTo(AppScreen::CustomizeLeds)
         } else {
-            Message::SelectCustomizeImage(DEFAULT_PLACEHOLDER_IMAGE.clone())
+            Message::SelectCustomizeImage(assets::handle(Asset::BadgePlaceholder))
         };
 
         let finish_button = button(text("Next").size(BUTTON_TEXT_SIZE))
@@ -545,7 +2527,7 @@ impl BuildABadgeApp {
         // Create main content without buttons
         let main_content = column![
             Space::with_height(Length::Fixed(50.0)),
-            image(STUFF_ME_ICON.clone())
+            image(assets::handle(Asset::StuffMeIcon))
                 .width(Length::Fixed(120.0))
                 .height(Length::Fixed(120.0)),
             text("Stuff Me")
@@ -556,12 +2538,20 @@ impl BuildABadgeApp {
                 .horizontal_alignment(iced::alignment::Horizontal::Center),
             Space::new(Length::Shrink, Length::Fixed(10.0)),
             row![
-                user_image_container,
+                preview_column,
                 column![
                     choose_image_text,
-                    Space::new(Length::Shrink, Length::Fixed(20.0)),
+                    picture_status,
+                    Space::new(Length::Shrink, Length::Fixed(10.0)),
                     image_selection_row,
+                    self.render_image_library(),
                 ]
+                .push_maybe(
+                    self.selected_customize_image
+                        .is_some()
+                        .then(|| self.render_decoration_controls())
+                        .flatten(),
+                )
                 .width(Length::FillPortion(3))
                 .align_items(Alignment::Center)
                 .spacing(10)
@@ -594,40 +2584,328 @@ impl BuildABadgeApp {
         .into()
     }
 
-    fn render_customize_leds_screen(&self) -> Element<Message> {
-        let back_button = button(text("Back").size(BUTTON_TEXT_SIZE))
-            .on_press(Message::NavigateTo(AppScreen::CustomizeBadge))
-            .padding([10, 40])
-            .style(theme_fn(YellowButtonStyle));
+    /// Sliders that tune the picture for the badge's screen.
+    fn render_adjustment_controls(&self) -> Element<'_, Message> {
+        let adjustments = self.adjustments;
+        let adjustment_slider = |label: &str,
+                                 range: std::ops::RangeInclusive<f32>,
+                                 value: f32,
+                                 set: fn(&mut Adjustments, f32)| {
+            row![
+                text(label.to_string()).size(14).width(Length::Fixed(80.0)),
+                slider(range, value, move |value| {
+                    let mut changed = adjustments;
+                    set(&mut changed, value);
+                    Message::AdjustPicture(changed)
+                })
+                .step(0.05),
+                text(format!("{:+.2}", value)).size(14).width(Length::Fixed(45.0)),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        };
 
-        let next_button = button(text("Next").size(BUTTON_TEXT_SIZE))
-            .on_press(Message::NavigateTo(AppScreen::NameBadge))
-            .padding([10, 40])
-            .style(theme_fn(YellowButtonStyle));
+        let preset_button = |label: &str, preset: Adjustments| {
+            button(text(label.to_string()).size(14))
+                .on_press_maybe((adjustments != preset).then_some(Message::AdjustPicture(preset)))
+                .padding([5, 15])
+                .style(if adjustments != preset {
+                    theme_fn(YellowButtonStyle)
+                } else {
+                    theme_fn(DisabledButtonStyle)
+                })
+        };
 
-        let modes = [
-            LedMode::Manual,
-            LedMode::Rainbow,
-            LedMode::Snowstorm,
-            LedMode::RedChase,
-            LedMode::RainbowChase,
-            LedMode::BlueChase,
-            LedMode::GreenDot,
-            LedMode::BlueDot,
-            LedMode::BlueSin,
-            LedMode::WhiteFade,
-            LedMode::BarGraph,
-            LedMode::Zylon,
-            LedMode::Audio,
-            LedMode::Accel,
-        ];
+        let error = self
+            .adjust_error
+            .as_ref()
+            .map(|(_, _, _, error)| {
+                text(error)
+                    .size(14)
+                    .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0)))
+            });
 
-        // Create two columns for better layout
-        let radio_buttons = modes.chunks(7).enumerate().fold(
-            row!().spacing(80).align_items(Alignment::Start),
-            |row_acc, (_col_idx, chunk)| {
-                let column = chunk.iter().fold(
-                    column!().spacing(18).align_items(Alignment::Start),
+        column![
+            adjustment_slider("Brightness", -1.0..=1.0, adjustments.brightness, |a, v| {
+                a.brightness = v
+            }),
+            adjustment_slider("Contrast", -1.0..=1.0, adjustments.contrast, |a, v| {
+                a.contrast = v
+            }),
+            adjustment_slider("Saturation", -1.0..=1.0, adjustments.saturation, |a, v| {
+                a.saturation = v
+            }),
+            adjustment_slider("Gamma", 0.5..=2.0, adjustments.gamma, |a, v| a.gamma = v),
+            row![
+                preset_button("Badge display", Adjustments::BADGE_DISPLAY),
+                preset_button("Reset", Adjustments::default()),
+            ]
+            .spacing(10),
+        ]
+        .push_maybe(error)
+        .spacing(4)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    /// The theme pack's frames and stickers, and the layers made from them.
+    /// `None` when the pack has no decorations.
+    fn render_decoration_controls(&self) -> Option<Element<'_, Message>> {
+        let pack = theme_pack::active();
+        if pack.frames.is_empty() && pack.stickers.is_empty() {
+            return None;
+        }
+
+        let decoration_row = |label: &str, kind: LayerKind, width: f32| {
+            pack.decorations(kind).iter().fold(
+                row![text(label.to_string()).size(14).width(Length::Fixed(70.0))]
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+                |tiles, decoration| {
+                    tiles.push(
+                        button(
+                            image(decoration.handle.clone())
+                                .width(Length::Fixed(width))
+                                .height(Length::Fixed(45.0))
+                                .content_fit(ContentFit::Contain),
+                        )
+                        .on_press(Message::AddLayer(kind, decoration.name.clone()))
+                        .padding(4)
+                        .style(theme_fn(DefaultBadgeStyle)),
+                    )
+                },
+            )
+        };
+
+        // Listed top first, as they're stacked on the picture
+        let layer_list = self.layers.iter().enumerate().rev().fold(
+            row![].spacing(6).align_items(Alignment::Center),
+            |list, (index, layer)| {
+                list.push(
+                    button(text(&layer.name).size(14))
+                        .on_press(Message::SelectLayer(index))
+                        .padding([5, 10])
+                        .style(if self.selected_layer == Some(index) {
+                            theme_fn(SelectedBadgeStyle)
+                        } else {
+                            theme_fn(DefaultBadgeStyle)
+                        }),
+                )
+            },
+        );
+
+        let selected = self
+            .selected_layer
+            .and_then(|index| Some((index, self.layers.get(index)?.clone())));
+        let layer_controls = selected.map(|(index, layer)| {
+            let layer_slider = |label: &str,
+                                range: std::ops::RangeInclusive<f32>,
+                                step: f32,
+                                value: f32,
+                                set: fn(&mut Layer, f32)| {
+                let layer = layer.clone();
+                row![
+                    text(label.to_string()).size(14).width(Length::Fixed(80.0)),
+                    slider(range, value, move |value| {
+                        let mut changed = layer.clone();
+                        set(&mut changed, value);
+                        Message::ChangeLayer(index, changed)
+                    })
+                    .step(step),
+                    text(format!("{:.0}", value)).size(14).width(Length::Fixed(45.0)),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center)
+            };
+            let order_button = |label: &str, message: Option<Message>| {
+                button(text(label.to_string()).size(14))
+                    .style(if message.is_some() {
+                        theme_fn(YellowButtonStyle)
+                    } else {
+                        theme_fn(DisabledButtonStyle)
+                    })
+                    .on_press_maybe(message)
+                    .padding([5, 15])
+            };
+
+            let mut controls = column![].spacing(4);
+            if layer.kind == LayerKind::Sticker {
+                let (width, height) = (fwi::SCREEN_WIDTH as f32, fwi::SCREEN_HEIGHT as f32);
+                controls = controls
+                    .push(layer_slider("Left/right", 0.0..=width, 1.0, layer.x, |l, v| l.x = v))
+                    .push(layer_slider("Up/down", 0.0..=height, 1.0, layer.y, |l, v| l.y = v))
+                    .push(layer_slider(
+                        "Size %",
+                        layers::MIN_SCALE * 100.0..=layers::MAX_SCALE * 100.0,
+                        5.0,
+                        layer.scale * 100.0,
+                        |l, v| l.scale = v / 100.0,
+                    ))
+                    .push(layer_slider("Turn °", -180.0..=180.0, 1.0, layer.rotation, |l, v| {
+                        l.rotation = v
+                    }));
+            }
+            controls.push(
+                row![
+                    order_button(
+                        "Bring forward",
+                        (index + 1 < self.layers.len()).then_some(Message::RaiseLayer(index)),
+                    ),
+                    order_button(
+                        "Send back",
+                        (index > 0).then_some(Message::LowerLayer(index)),
+                    ),
+                    order_button("Remove", Some(Message::RemoveLayer(index))),
+                ]
+                .spacing(10),
+            )
+        });
+
+        Some(
+            column![
+                text("Decorate your picture").size(BODY_SIZE),
+            ]
+            .push_maybe(
+                (!pack.frames.is_empty())
+                    .then(|| decoration_row("Frames", LayerKind::Frame, 60.0)),
+            )
+            .push_maybe(
+                (!pack.stickers.is_empty())
+                    .then(|| decoration_row("Stickers", LayerKind::Sticker, 45.0)),
+            )
+            .push(layer_list)
+            .push_maybe(layer_controls)
+            .spacing(8)
+            .align_items(Alignment::Center)
+            .into(),
+        )
+    }
+
+    /// Searchable, paged thumbnails of the image library folder.
+    fn render_image_library(&self) -> Element<'_, Message> {
+        let search = self.library_search.trim().to_lowercase();
+        let matches: Vec<&LibraryImage> = self
+            .library_images
+            .iter()
+            .filter(|picture| picture.name.to_lowercase().contains(&search))
+            .collect();
+        let page_count = matches.len().div_ceil(LIBRARY_PAGE_SIZE).max(1);
+        let page = self.library_page.min(page_count - 1);
+
+        let header = row![
+            text("More pictures").size(BODY_SIZE),
+            text_input("Search", &self.library_search)
+                .on_input(Message::LibrarySearch)
+                .size(14)
+                .width(Length::Fixed(200.0)),
+            button(
+                text(if self.is_scanning_library {
+                    "Scanning..."
+                } else {
+                    "Rescan"
+                })
+                .size(14),
+            )
+            .on_press_maybe((!self.is_scanning_library).then_some(Message::LibraryRescan))
+            .padding([5, 15])
+            .style(theme_fn(YellowButtonStyle)),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let status = match &self.library_error {
+            Some(error) => text(error)
+                .size(14)
+                .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+            None if self.library_images.is_empty() && !self.is_scanning_library => text(format!(
+                "Put pictures in {} to see them here",
+                image_library::library_dir().display()
+            ))
+            .size(14),
+            None if matches.is_empty() && !search.is_empty() => {
+                text("No pictures match your search").size(14)
+            }
+            None => text(""),
+        };
+
+        let grid = matches
+            .iter()
+            .skip(page * LIBRARY_PAGE_SIZE)
+            .take(LIBRARY_PAGE_SIZE)
+            .collect::<Vec<_>>()
+            .chunks(LIBRARY_COLUMNS)
+            .fold(column![].spacing(8), |grid, pictures| {
+                grid.push(pictures.iter().fold(row![].spacing(8), |tiles, picture| {
+                    let is_selected =
+                        self.selected_customize_image.as_ref() == Some(&picture.handle());
+                    tiles.push(
+                        button(
+                            column![
+                                image(picture.thumbnail.clone())
+                                    .width(Length::Fixed(80.0))
+                                    .height(Length::Fixed(60.0))
+                                    .content_fit(ContentFit::Contain),
+                                text(&picture.name).size(12),
+                            ]
+                            .spacing(2)
+                            .align_items(Alignment::Center),
+                        )
+                        .on_press(Message::ConvertPicture(picture.path.clone()))
+                        .padding(4)
+                        .width(Length::Fixed(96.0))
+                        .style(if is_selected {
+                            theme_fn(SelectedBadgeStyle)
+                        } else {
+                            theme_fn(DefaultBadgeStyle)
+                        }),
+                    )
+                }))
+            });
+
+        let page_button = |label: &str, target: Option<usize>| {
+            button(text(label.to_string()).size(14))
+                .on_press_maybe(target.map(Message::LibraryPage))
+                .padding([5, 15])
+                .style(if target.is_some() {
+                    theme_fn(YellowButtonStyle)
+                } else {
+                    theme_fn(DisabledButtonStyle)
+                })
+        };
+        let pagination = (page_count > 1).then(|| {
+            row![
+                page_button("Previous", page.checked_sub(1)),
+                text(format!("Page {} of {}", page + 1, page_count)).size(14),
+                page_button("Next", (page + 1 < page_count).then_some(page + 1)),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        });
+
+        column![header, status, scrollable(grid).height(Length::Shrink)]
+            .push_maybe(pagination)
+            .spacing(8)
+            .align_items(Alignment::Center)
+            .into()
+    }
+
+    fn render_customize_leds_screen(&self) -> Element<Message> {
+        let back_button = button(text("Back").size(BUTTON_TEXT_SIZE))
+            .on_press(Message::NavigateTo(AppScreen::CustomizeBadge))
+            .padding([10, 40])
+            .style(theme_fn(YellowButtonStyle));
+
+        let next_button = button(text("Next").size(BUTTON_TEXT_SIZE))
+            .on_press(Message::NavigateTo(AppScreen::NameBadge))
+            .padding([10, 40])
+            .style(theme_fn(YellowButtonStyle));
+
+        // Create two columns for better layout
+        let radio_buttons = LedMode::ALL.chunks(7).enumerate().fold(
+            row!().spacing(80).align_items(Alignment::Start),
+            |row_acc, (_col_idx, chunk)| {
+                let column = chunk.iter().fold(
+                    column!().spacing(18).align_items(Alignment::Start),
                     |col_acc, mode| {
                         col_acc.push(
                             radio(
@@ -648,7 +2926,7 @@ impl BuildABadgeApp {
         // Create main content without buttons
         let main_content = column![
             Space::with_height(Length::Fixed(50.0)),
-            image(LIGHT_ME_ICON.clone())
+            image(assets::handle(Asset::LightMeIcon))
                 .width(Length::Fixed(120.0))
                 .height(Length::Fixed(120.0)),
             text("Light Me")
@@ -657,7 +2935,7 @@ impl BuildABadgeApp {
             container(
                 text("Customize your Badge LEDs")
                     .size(HEADING_SIZE)
-                    .style(iced::theme::Text::Color(*BLUE_TEXT))
+                    .style(iced::theme::Text::Color(text_color()))
                     .horizontal_alignment(iced::alignment::Horizontal::Center)
                     .width(Length::Fill)
             )
@@ -694,14 +2972,25 @@ impl BuildABadgeApp {
             .padding([10, 40])
             .style(theme_fn(YellowButtonStyle));
 
+        let name_errors = validation::validate_badge_name(&self.badge_name);
+        let submit_enabled = name_errors.is_empty();
+
         let submit_button = button(text("Submit").size(BUTTON_TEXT_SIZE))
-            .on_press(Message::NavigateTo(AppScreen::Summary))
+            .on_press_maybe(if submit_enabled {
+                Some(Message::NavigateTo(AppScreen::ChooseApp))
+            } else {
+                None
+            })
             .padding([10, 40])
-            .style(theme_fn(YellowButtonStyle));
+            .style(if submit_enabled {
+                theme_fn(YellowButtonStyle)
+            } else {
+                theme_fn(DisabledButtonStyle)
+            });
 
         // Create the badge image container with consistent sizing
         let badge_image_container = container(
-            image(NAME_ME_IMAGE.clone())
+            image(assets::handle(Asset::BadgePlaceholder))
                 .width(Length::Fixed(300.0))
                 .height(Length::Fixed(300.0))
                 .content_fit(ContentFit::ScaleDown),
@@ -713,9 +3002,9 @@ impl BuildABadgeApp {
         .style(theme_fn_container(UserImageBorderStyle));
 
         // Create the input section with better spacing and centering
-        let character_count = self.badge_name.len();
-        let characters_remaining = 20_usize.saturating_sub(character_count);
-        let counter_color = if characters_remaining <= 2 {
+        let character_count = self.badge_name.chars().count();
+        let characters_remaining = validation::MAX_NAME_CHARS.saturating_sub(character_count);
+        let counter_color = if !name_errors.is_empty() || characters_remaining <= 2 {
             Color::from_rgb8(200, 0, 0) // Red when close to limit
         } else if characters_remaining <= 3 {
             Color::from_rgb8(255, 140, 0) // Orange when getting close
@@ -723,6 +3012,17 @@ impl BuildABadgeApp {
             Color::from_rgb8(100, 100, 100) // Gray when plenty of room
         };
 
+        let mut name_input =
+            text_input::<_, Theme, iced::Renderer>("Enter name...", &self.badge_name)
+                .on_input(Message::BadgeNameChanged)
+                .padding(15)
+                .size(BODY_SIZE)
+                .width(Length::Fixed(300.0))
+                .id(text_input::Id::new(BADGE_NAME_INPUT_ID));
+        if submit_enabled {
+            name_input = name_input.on_submit(Message::NavigateTo(AppScreen::ChooseApp));
+        }
+
         let input_section = container(
             column![
                 text("Type Your Badge Name Below")
@@ -735,15 +3035,7 @@ impl BuildABadgeApp {
                     .style(iced::theme::Text::Color(Color::from_rgb8(100, 100, 100)))
                     .width(Length::Fill),
                 Space::new(Length::Shrink, Length::Fixed(20.0)),
-                container(
-                    text_input::<_, Theme, iced::Renderer>("Enter name...", &self.badge_name)
-                        .on_input(Message::BadgeNameChanged)
-                        .on_submit(Message::NavigateTo(AppScreen::Summary))
-                        .padding(15)
-                        .size(BODY_SIZE)
-                        .width(Length::Fixed(300.0))
-                        .id(text_input::Id::new(BADGE_NAME_INPUT_ID))
-                )
+                container(name_input)
                 .width(Length::Fill)
                 .center_x(),
                 Space::new(Length::Shrink, Length::Fixed(10.0)),
@@ -752,6 +3044,15 @@ impl BuildABadgeApp {
                     .horizontal_alignment(iced::alignment::Horizontal::Center)
                     .style(iced::theme::Text::Color(counter_color))
                     .width(Length::Fill),
+                name_errors.iter().fold(column![].spacing(3), |col_acc, error| {
+                    col_acc.push(
+                        text(error.to_string())
+                            .size(14)
+                            .horizontal_alignment(iced::alignment::Horizontal::Center)
+                            .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0)))
+                            .width(Length::Fill),
+                    )
+                }),
             ]
             .align_items(Alignment::Center)
             .spacing(5),
@@ -763,7 +3064,7 @@ impl BuildABadgeApp {
         // Create main content without buttons
         let main_content = column![
             Space::with_height(Length::Fixed(30.0)),
-            image(PLACE_ME_ICON.clone())
+            image(assets::handle(Asset::PlaceMeIcon))
                 .width(Length::Fixed(120.0))
                 .height(Length::Fixed(120.0)),
             text("Place Me")
@@ -806,19 +3107,121 @@ impl BuildABadgeApp {
         .into()
     }
 
-    fn render_summary_screen(&self) -> Element<Message> {
+    fn render_choose_app_screen(&self) -> Element<'_, Message> {
         let back_button = button(text("Back").size(BUTTON_TEXT_SIZE))
             .on_press(Message::NavigateTo(AppScreen::NameBadge))
             .padding([10, 40])
             .style(theme_fn(YellowButtonStyle));
 
+        let next_button = button(text("Next").size(BUTTON_TEXT_SIZE))
+            .on_press(Message::NavigateTo(AppScreen::Summary))
+            .padding([10, 40])
+            .style(theme_fn(YellowButtonStyle));
+
+        let app_list = self.wasm_apps.iter().enumerate().fold(
+            column!().spacing(18).align_items(Alignment::Start),
+            |col_acc, (index, app)| {
+                let label = if app.is_bundled {
+                    app.name.clone()
+                } else {
+                    format!("{} (your app)", app.name)
+                };
+                col_acc.push(
+                    column![
+                        radio(
+                            label,
+                            index,
+                            Some(self.selected_wasm_app),
+                            Message::SelectWasmApp,
+                        )
+                        .size(20)
+                        .spacing(10),
+                        text(&app.description)
+                            .size(14)
+                            .style(iced::theme::Text::Color(Color::from_rgb8(120, 120, 120))),
+                    ]
+                    .spacing(4),
+                )
+            },
+        );
+
+        let add_button = button(text("Add your own .wasm...").size(BODY_SIZE))
+            .on_press(Message::AddWasmApp)
+            .padding([8, 20])
+            .style(theme_fn(YellowButtonStyle));
+
+        let error_text = text(self.wasm_app_error.clone().unwrap_or_default())
+            .size(14)
+            .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0)));
+
+        // Create main content without buttons
+        let main_content = column![
+            Space::with_height(Length::Fixed(50.0)),
+            image(assets::handle(Asset::PlugMeIcon))
+                .width(Length::Fixed(120.0))
+                .height(Length::Fixed(120.0)),
+            text("Plug Me")
+                .size(HEADING_SIZE)
+                .horizontal_alignment(iced::alignment::Horizontal::Center),
+            container(
+                text("Choose the app your Badge runs")
+                    .size(HEADING_SIZE)
+                    .style(iced::theme::Text::Color(text_color()))
+                    .horizontal_alignment(iced::alignment::Horizontal::Center)
+                    .width(Length::Fill)
+            )
+            .padding([0, 50]),
+            Space::new(Length::Shrink, Length::Fixed(40.0)),
+            container(scrollable(app_list).height(Length::Fixed(260.0)))
+                .width(Length::Fixed(600.0)),
+            add_button,
+            error_text,
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .width(Length::Fill);
+
+        // Create bottom navigation buttons positioned at bottom corners
+        let bottom_navigation = container(
+            row![back_button, Space::with_width(Length::Fill), next_button,]
+                .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(20);
+
+        column![
+            container(main_content)
+                .width(Length::Fill)
+                .height(Length::Fill),
+            bottom_navigation
+        ]
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn render_summary_screen(&self) -> Element<Message> {
+        let back_button = button(text("Back").size(BUTTON_TEXT_SIZE))
+            .on_press(Message::NavigateTo(AppScreen::ChooseApp))
+            .padding([10, 40])
+            .style(theme_fn(YellowButtonStyle));
+
         let configure_button_text = if self.is_configuring {
             "Configuring..."
         } else {
             "Configure Device"
         };
 
-        let configure_button_enabled = !self.is_configuring;
+        let mut settings_errors = self.device_settings.validate(&self.badge_name);
+        settings_errors.extend(
+            validation::validate_badge_name(&self.badge_name)
+                .iter()
+                .map(ToString::to_string),
+        );
+        let configure_button_enabled = !self.is_configuring
+            && !self.is_backing_up
+            && !self.is_picture_pending()
+            && settings_errors.is_empty();
         let configure_button_style = if configure_button_enabled {
             theme_fn(YellowButtonStyle)
         } else {
@@ -839,188 +3242,1117 @@ impl BuildABadgeApp {
             .padding([10, 40])
             .style(theme_fn(YellowButtonStyle));
 
-        // Summary content
-        let selected_image_display = match &self.selected_customize_image {
-            Some(handle) => container(
-                image(handle.clone())
-                    .width(Length::Fixed(120.0))
-                    .height(Length::Fixed(120.0))
-                    .content_fit(ContentFit::ScaleDown),
-            )
-            .width(Length::Fixed(140.0))
-            .height(Length::Fixed(140.0))
-            .center_x()
-            .center_y()
-            .style(theme_fn_container(UserImageBorderStyle)),
-            None => container(
-                text("No Image\nSelected")
-                    .size(16)
-                    .horizontal_alignment(iced::alignment::Horizontal::Center)
-                    .style(iced::theme::Text::Color(Color::from_rgb8(150, 150, 150))),
-            )
-            .width(Length::Fixed(140.0))
-            .height(Length::Fixed(140.0))
-            .center_x()
-            .center_y()
-            .style(theme_fn_container(UserImageBorderStyle)),
-        };
+        let advanced_settings_button = button(text("Advanced Settings").size(BODY_SIZE))
+            .on_press_maybe(if self.is_configuring {
+                None
+            } else {
+                Some(Message::NavigateTo(AppScreen::AdvancedSettings))
+            })
+            .padding([8, 20])
+            .style(if self.is_configuring {
+                theme_fn(DisabledButtonStyle)
+            } else {
+                theme_fn(YellowButtonStyle)
+            });
+
+        let settings_summary_text = match self.device_settings.matching_preset() {
+            Some(preset) => preset.display_name().to_string(),
+            None => "Custom".to_string(),
+        };
+
+        let wifi_join_display = match &self.wifi_qr_code {
+            Some(qr_handle) if self.device_settings.is_wifi_secured() => row![
+                image(qr_handle.clone())
+                    .width(Length::Fixed(140.0))
+                    .height(Length::Fixed(140.0)),
+                column![
+                    text("Join the badge's Wi-Fi").size(BODY_SIZE),
+                    row![
+                        text("Network: ").size(BODY_SIZE),
+                        text(self.device_settings.effective_ssid(&self.badge_name))
+                            .size(BODY_SIZE)
+                            .style(iced::theme::Text::Color(text_color())),
+                    ],
+                    row![
+                        text("Passphrase: ").size(BODY_SIZE),
+                        text(&self.device_settings.wifi_ap_passphrase)
+                            .size(BODY_SIZE)
+                            .style(iced::theme::Text::Color(text_color())),
+                    ],
+                ]
+                .spacing(5),
+            ]
+            .spacing(15)
+            .align_items(Alignment::Center),
+            _ => row![],
+        };
+
+        // Summary content
+        let selected_image_display = match &self.selected_customize_image {
+            // What gets uploaded, with the sliders and layers applied
+            Some(handle) => container(
+                image(
+                    self.adjusted_picture()
+                        .map_or_else(|| shown_picture(handle), |picture| picture.preview.clone()),
+                )
+                .width(Length::Fixed(120.0))
+                .height(Length::Fixed(120.0))
+                .content_fit(ContentFit::ScaleDown),
+            )
+            .width(Length::Fixed(140.0))
+            .height(Length::Fixed(140.0))
+            .center_x()
+            .center_y()
+            .style(theme_fn_container(UserImageBorderStyle)),
+            None => container(
+                text("No Image\nSelected")
+                    .size(16)
+                    .horizontal_alignment(iced::alignment::Horizontal::Center)
+                    .style(iced::theme::Text::Color(Color::from_rgb8(150, 150, 150))),
+            )
+            .width(Length::Fixed(140.0))
+            .height(Length::Fixed(140.0))
+            .center_x()
+            .center_y()
+            .style(theme_fn_container(UserImageBorderStyle)),
+        };
+
+        let selected_led_text = match &self.selected_led_mode {
+            Some(mode) => mode.display_name(),
+            None => "No LED Pattern Selected",
+        };
+
+        let badge_name_text = if self.badge_name.is_empty() {
+            "No Name Entered"
+        } else {
+            &self.badge_name
+        };
+
+        // Create main content without buttons
+        let main_content = column![
+            Space::with_height(Length::Fixed(15.0)),
+            text("Configuration Summary")
+                .size(HEADING_SIZE)
+                .horizontal_alignment(iced::alignment::Horizontal::Center),
+            Space::new(Length::Shrink, Length::Fixed(30.0)),
+            container(
+                column![
+                    // Badge Image Section
+                    column![
+                        text("Badge Image:")
+                            .size(BODY_SIZE + 2)
+                            .horizontal_alignment(iced::alignment::Horizontal::Center),
+                        Space::new(Length::Shrink, Length::Fixed(10.0)),
+                        container(selected_image_display)
+                            .width(Length::Fill)
+                            .center_x(),
+                    ]
+                    .align_items(Alignment::Center)
+                    .spacing(5),
+                    Space::new(Length::Shrink, Length::Fixed(25.0)),
+                    // LED Pattern and Badge Name Section
+                    column![
+                        row![
+                            text("LED Pattern: ").size(BODY_SIZE + 2),
+                            text(selected_led_text)
+                                .size(BODY_SIZE + 2)
+                                .style(iced::theme::Text::Color(text_color())),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        Space::new(Length::Shrink, Length::Fixed(15.0)),
+                        row![
+                            text("Badge Name: ").size(BODY_SIZE + 2),
+                            text(badge_name_text)
+                                .size(BODY_SIZE + 2)
+                                .style(iced::theme::Text::Color(text_color())),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        Space::new(Length::Shrink, Length::Fixed(15.0)),
+                        row![
+                            text("Badge App: ").size(BODY_SIZE + 2),
+                            text(&self.wasm_app().name)
+                                .size(BODY_SIZE + 2)
+                                .style(iced::theme::Text::Color(text_color())),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        Space::new(Length::Shrink, Length::Fixed(15.0)),
+                        row![
+                            text("Device Settings: ").size(BODY_SIZE + 2),
+                            text(settings_summary_text)
+                                .size(BODY_SIZE + 2)
+                                .style(iced::theme::Text::Color(text_color())),
+                            advanced_settings_button,
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        Space::new(Length::Shrink, Length::Fixed(15.0)),
+                        wifi_join_display,
+                    ]
+                    .align_items(Alignment::Center)
+                ]
+                .align_items(Alignment::Center)
+            )
+            .padding(30)
+            .style(theme_fn_container(SummaryBoxStyle)),
+            Space::new(Length::Shrink, Length::Fixed(20.0)),
+            self.render_configuration_diff(),
+            Space::new(Length::Shrink, Length::Fixed(20.0)),
+            // Configuration section
+            column![
+                text("Device Configuration")
+                    .size(HEADING_SIZE - 5)
+                    .horizontal_alignment(iced::alignment::Horizontal::Center),
+                Space::new(Length::Shrink, Length::Fixed(20.0)),
+                configure_button,
+                if settings_errors.is_empty() {
+                    text("")
+                } else {
+                    text("Fix the badge name or advanced settings before configuring the device")
+                        .size(14)
+                        .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0)))
+                },
+                self.render_backup_controls(),
+                Space::new(Length::Shrink, Length::Fixed(20.0)),
+                if self.is_configuring || self.configuration_progress > 0.0 {
+                    let status_text = if self.is_configuring {
+                        &self.configuration_status
+                    } else if self.configuration_error.is_some() {
+                        "Configuration failed - see details below"
+                    } else if self.configuration_progress >= 1.0 {
+                        "Configuration Complete! You can configure again anytime."
+                    } else {
+                        &format!("{}%", (self.configuration_progress * 100.0) as u32)
+                    };
+
+                    let mut status_column = column![
+                        progress_bar(0.0..=1.0, self.configuration_progress)
+                            .width(Length::Fixed(400.0))
+                            .height(Length::Fixed(20.0)),
+                        Space::new(Length::Shrink, Length::Fixed(10.0)),
+                        text(status_text)
+                            .size(16)
+                            .horizontal_alignment(iced::alignment::Horizontal::Center)
+                    ];
+
+                    // Add console output and error display side by side if there's any
+                    let has_console = !self.configuration_console_output.is_empty();
+                    let has_error = self.configuration_error.is_some();
+                    
+                    if has_console || has_error {
+                        status_column = status_column.push(Space::new(Length::Shrink, Length::Fixed(15.0)));
+                        
+                        // Create a horizontal row for console and error
+                        let mut console_row = row!().spacing(20).align_items(Alignment::Start);
+                        
+                        // Add console output if present
+                        if has_console {
+                            let console_column = column![
+                                text("Console Output:")
+                                    .size(14)
+                                    .style(iced::theme::Text::Color(text_color())),
+                                Space::new(Length::Shrink, Length::Fixed(5.0)),
+                                container(
+                                    text(&self.configuration_console_output)
+                                        .size(12)
+                                        .style(iced::theme::Text::Color(Color::from_rgb(0.9, 0.9, 0.9)))
+                                        .horizontal_alignment(iced::alignment::Horizontal::Left)
+                                )
+                                .width(Length::Fixed(if has_error { 480.0 } else { 800.0 }))
+                                .height(Length::Fixed(250.0))
+                                .padding(10)
+                                .style(theme_fn_container(ConsoleOutputStyle))
+                            ]
+                            .spacing(5);
+                            
+                            console_row = console_row.push(console_column);
+                        }
+                        
+                        // Add error message if present
+                        if let Some(error) = &self.configuration_error {
+                            let error_column = column![
+                                text("Error:")
+                                    .size(14)
+                                    .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+                                Space::new(Length::Shrink, Length::Fixed(5.0)),
+                                container(render_config_error(error))
+                                .width(Length::Fixed(if has_console { 480.0 } else { 800.0 }))
+                                .height(Length::Fixed(250.0))
+                                .padding(10)
+                                .style(theme_fn_container(ErrorBoxStyle))
+                            ]
+                            .spacing(5);
+                            
+                            console_row = console_row.push(error_column);
+                        }
+                        
+                        status_column = status_column.push(console_row);
+                    }
+
+                    if !self.is_configuring && self.run_log_path.is_some() {
+                        status_column = status_column
+                            .push(Space::new(Length::Shrink, Length::Fixed(10.0)))
+                            .push(self.render_run_log_controls());
+                    }
+
+                    if self.app_log_streaming || !self.app_log_lines.is_empty() {
+                        status_column = status_column
+                            .push(Space::new(Length::Shrink, Length::Fixed(15.0)))
+                            .push(self.render_app_log());
+                    }
+
+                    container(status_column.align_items(Alignment::Center))
+                        .width(Length::Fill)
+                        .center_x()
+                } else {
+                    container(Space::new(Length::Shrink, Length::Fixed(50.0)))
+                }
+            ]
+            .align_items(Alignment::Center)
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .width(Length::Fill);
+
+        // Create bottom navigation buttons positioned at bottom corners
+        let bottom_navigation = container(
+            row![back_button, Space::with_width(Length::Fill), done_button,]
+                .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(20);
+
+        column![
+            container(main_content)
+                .width(Length::Fill)
+                .height(Length::Fill),
+            bottom_navigation
+        ]
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn render_app_log(&self) -> Element<'_, Message> {
+        let small_button = |label: &str, message: Option<Message>| {
+            let enabled = message.is_some();
+            button(text(label.to_string()).size(14))
+                .on_press_maybe(message)
+                .padding([5, 15])
+                .style(if enabled {
+                    theme_fn(YellowButtonStyle)
+                } else {
+                    theme_fn(DisabledButtonStyle)
+                })
+        };
+
+        let title = if self.app_log_paused_at.is_some() {
+            format!(
+                "Badge Output (paused, {} new):",
+                self.app_log_lines.len() - self.app_log_paused_at.unwrap_or(0)
+            )
+        } else {
+            "Badge Output:".to_string()
+        };
+
+        let header = row![
+            text(title)
+                .size(14)
+                .style(iced::theme::Text::Color(text_color())),
+            Space::with_width(Length::Fill),
+            small_button(
+                if self.app_log_paused_at.is_some() { "Resume" } else { "Pause" },
+                self.app_log_streaming.then_some(Message::AppLogTogglePause),
+            ),
+            small_button("Clear", Some(Message::AppLogClear)),
+            small_button("Stop", self.app_log_streaming.then_some(Message::AppLogStop)),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let visible = self.app_log_paused_at.unwrap_or(self.app_log_lines.len());
+        let lines = self.app_log_lines[..visible].iter().map(|line| {
+            let color = match line.level {
+                LogLevel::Error => Color::from_rgb8(255, 100, 100),
+                LogLevel::Warn => *YELLOW,
+                LogLevel::Info => Color::from_rgb(0.9, 0.9, 0.9),
+                LogLevel::Debug => Color::from_rgb(0.55, 0.55, 0.55),
+            };
+            row![
+                text(line.timestamp())
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(iced::theme::Text::Color(Color::from_rgb(0.55, 0.55, 0.55))),
+                text(&line.text)
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(iced::theme::Text::Color(color)),
+            ]
+            .spacing(10)
+            .into()
+        });
+
+        let body: Element<Message> = if visible == 0 {
+            text("Waiting for the badge to print something...")
+                .size(12)
+                .style(iced::theme::Text::Color(Color::from_rgb(0.55, 0.55, 0.55)))
+                .into()
+        } else {
+            scrollable(column(lines).width(Length::Fill))
+                .id(scrollable::Id::new(APP_LOG_SCROLL_ID))
+                .height(Length::Fill)
+                .into()
+        };
+
+        column![
+            header,
+            container(body)
+                .width(Length::Fixed(800.0))
+                .height(Length::Fixed(200.0))
+                .padding(10)
+                .style(theme_fn_container(ConsoleOutputStyle))
+        ]
+        .spacing(5)
+        .width(Length::Fixed(800.0))
+        .into()
+    }
+
+    fn render_backup_controls(&self) -> Element<'_, Message> {
+        let enabled = !self.is_configuring && !self.is_backing_up;
+        let small_button = |label: &str, message: Message| {
+            button(text(label.to_string()).size(BODY_SIZE))
+                .on_press_maybe(if enabled { Some(message) } else { None })
+                .padding([8, 20])
+                .style(if enabled {
+                    theme_fn(YellowButtonStyle)
+                } else {
+                    theme_fn(DisabledButtonStyle)
+                })
+        };
+
+        // Starting a backup clears the last export message and the other way round
+        let status = match self.backup_status.as_ref().or(self.bundle_status.as_ref()) {
+            _ if self.is_backing_up => text("Working with the badge...").size(14),
+            Some(Ok(message)) => text(message)
+                .size(14)
+                .style(iced::theme::Text::Color(text_color())),
+            Some(Err(error)) => text(error)
+                .size(14)
+                .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+            None => text(""),
+        };
+
+        column![
+            row![
+                small_button("Back up badge", Message::BackupBadge),
+                small_button("Restore backup...", Message::RestoreBackup),
+                small_button("Export design...", Message::ExportBundle),
+                button(text("Serial console").size(BODY_SIZE))
+                    .on_press(Message::NavigateTo(AppScreen::Console))
+                    .padding([8, 20])
+                    .style(theme_fn(YellowButtonStyle)),
+                button(text("Try in emulator").size(BODY_SIZE))
+                    .on_press(Message::RunEmulator)
+                    .padding([8, 20])
+                    .style(theme_fn(YellowButtonStyle)),
+            ]
+            .spacing(10),
+            status,
+        ]
+        .spacing(5)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    fn render_run_log_controls(&self) -> Element<'_, Message> {
+        let small_button = |label: &str, message: Message| {
+            button(text(label.to_string()).size(BODY_SIZE))
+                .on_press(message)
+                .padding([8, 20])
+                .style(theme_fn(YellowButtonStyle))
+        };
+
+        let status = match &self.run_log_status {
+            Some(Ok(message)) => text(message)
+                .size(14)
+                .style(iced::theme::Text::Color(text_color())),
+            Some(Err(error)) => text(error)
+                .size(14)
+                .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+            None => text("A detailed log of this run is kept for support requests").size(14),
+        };
+
+        column![
+            row![
+                small_button("Save log...", Message::SaveRunLog),
+                small_button("Open logs folder", Message::OpenLogsFolder),
+            ]
+            .spacing(10),
+            status,
+        ]
+        .spacing(5)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    fn render_configuration_diff(&self) -> Element<'_, Message> {
+        let compare_enabled = !self.is_configuring && !self.is_comparing_device;
+        let compare_button = button(
+            text(if self.is_comparing_device {
+                "Reading badge..."
+            } else if self.configuration_diff.is_some() {
+                "Compare again"
+            } else {
+                "Compare with badge"
+            })
+            .size(BODY_SIZE),
+        )
+        .on_press_maybe(if compare_enabled {
+            Some(Message::CompareWithBadge)
+        } else {
+            None
+        })
+        .padding([8, 20])
+        .style(if compare_enabled {
+            theme_fn(YellowButtonStyle)
+        } else {
+            theme_fn(DisabledButtonStyle)
+        });
+
+        let mut diff_column = column![compare_button]
+            .spacing(8)
+            .align_items(Alignment::Center);
+
+        if let Some(error) = &self.comparison_error {
+            diff_column = diff_column.push(
+                text(error)
+                    .size(14)
+                    .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+            );
+        }
+
+        if let Some(diffs) = &self.configuration_diff {
+            let changed_count = diffs.iter().filter(|diff| diff.is_changed()).count();
+            diff_column = diff_column.push(
+                text(if changed_count == 0 {
+                    "The badge already has this configuration".to_string()
+                } else {
+                    format!("{} field(s) will change on the badge", changed_count)
+                })
+                .size(16)
+                .style(iced::theme::Text::Color(text_color())),
+            );
+
+            let cell = |value: &str, width: f32, color: Color| {
+                text(value.to_string())
+                    .size(14)
+                    .width(Length::Fixed(width))
+                    .style(iced::theme::Text::Color(color))
+            };
+            let gray = Color::from_rgb8(100, 100, 100);
+            let changed = Color::from_rgb8(200, 0, 0);
+
+            let table = diffs.iter().filter(|diff| diff.is_changed()).fold(
+                column![row![
+                    cell("Field", 180.0, gray),
+                    cell("On badge", 220.0, gray),
+                    cell("After configure", 220.0, gray),
+                ]]
+                .spacing(4),
+                |col_acc, diff| {
+                    col_acc.push(row![
+                        cell(&diff.field, 180.0, Color::BLACK),
+                        cell(diff.device_text(), 220.0, gray),
+                        cell(diff.local_text(), 220.0, changed),
+                    ])
+                },
+            );
+
+            if changed_count > 0 {
+                diff_column = diff_column.push(
+                    container(table)
+                        .padding(10)
+                        .style(theme_fn_container(SummaryBoxStyle)),
+                );
+            }
+        }
+
+        diff_column.into()
+    }
+
+    fn render_file_browser_screen(&self) -> Element<'_, Message> {
+        let back_button = button(text("Back").size(BUTTON_TEXT_SIZE))
+            .on_press(Message::NavigateTo(AppScreen::Welcome))
+            .padding([10, 40])
+            .style(theme_fn(YellowButtonStyle));
+
+        let idle = !self.is_browser_busy;
+        let has_selection = self.browser_selected.is_some();
+        let action_button = |label: &str, message: Message, enabled: bool| {
+            button(text(label.to_string()).size(BODY_SIZE))
+                .on_press_maybe(if enabled { Some(message) } else { None })
+                .padding([8, 20])
+                .style(if enabled {
+                    theme_fn(YellowButtonStyle)
+                } else {
+                    theme_fn(DisabledButtonStyle)
+                })
+        };
+
+        let path_bar = row![
+            action_button(
+                "Up",
+                Message::BrowserOpen(device::parent_remote(&self.browser_path)),
+                idle && self.browser_path != "/",
+            ),
+            action_button(
+                "Refresh",
+                Message::BrowserOpen(self.browser_path.clone()),
+                idle,
+            ),
+            text(&self.browser_path)
+                .size(BODY_SIZE + 2)
+                .style(iced::theme::Text::Color(text_color())),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let entries_list = self.browser_entries.iter().fold(
+            column![].spacing(4).width(Length::Fill),
+            |col_acc, entry| {
+                let is_selected = self.browser_selected.as_deref() == Some(entry.name.as_str());
+                let size_text = match (entry.is_dir, entry.size) {
+                    (true, _) => "folder".to_string(),
+                    (false, Some(size)) if size >= 1024 => format!("{:.1} KB", size as f64 / 1024.0),
+                    (false, Some(size)) => format!("{} B", size),
+                    (false, None) => String::new(),
+                };
+                col_acc.push(
+                    button(
+                        row![
+                            text(if entry.is_dir {
+                                format!("{}/", entry.name)
+                            } else {
+                                entry.name.clone()
+                            })
+                            .size(BODY_SIZE)
+                            .width(Length::Fill),
+                            text(size_text)
+                                .size(14)
+                                .style(iced::theme::Text::Color(Color::from_rgb8(100, 100, 100))),
+                        ]
+                        .align_items(Alignment::Center),
+                    )
+                    .on_press(Message::BrowserSelect(entry.name.clone()))
+                    .padding([6, 12])
+                    .width(Length::Fill)
+                    .style(if is_selected {
+                        theme_fn(SelectedBadgeStyle)
+                    } else {
+                        theme_fn(DefaultBadgeStyle)
+                    }),
+                )
+            },
+        );
+
+        let listing: Element<Message> = if self.browser_entries.is_empty() {
+            text(if self.is_browser_busy {
+                "Reading badge..."
+            } else {
+                "This folder is empty"
+            })
+            .size(BODY_SIZE)
+            .style(iced::theme::Text::Color(Color::from_rgb8(100, 100, 100)))
+            .into()
+        } else {
+            scrollable(entries_list).height(Length::Fill).into()
+        };
+
+        let actions = row![
+            action_button("Download", Message::BrowserDownload, idle && has_selection),
+            action_button("Upload here", Message::BrowserUpload, idle),
+            action_button(
+                if self.browser_confirm_delete {
+                    "Confirm delete"
+                } else {
+                    "Delete"
+                },
+                Message::BrowserDelete,
+                idle && has_selection,
+            ),
+            text_input::<_, Theme, iced::Renderer>("New name", &self.browser_rename_to)
+                .on_input(Message::BrowserRenameChanged)
+                .padding(8)
+                .size(BODY_SIZE)
+                .width(Length::Fixed(240.0)),
+            action_button(
+                "Rename",
+                Message::BrowserRename,
+                idle && has_selection
+                    && !self.browser_rename_to.is_empty()
+                    && !self.browser_rename_to.contains('/')
+                    && self.browser_selected.as_deref() != Some(self.browser_rename_to.as_str()),
+            ),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let status = match &self.browser_status {
+            Some(Ok(message)) => text(message)
+                .size(14)
+                .style(iced::theme::Text::Color(text_color())),
+            Some(Err(error)) => text(error)
+                .size(14)
+                .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+            None => text("Select a file, or select a folder twice to open it").size(14),
+        };
+
+        // Create main content without buttons
+        let main_content = column![
+            Space::with_height(Length::Fixed(30.0)),
+            text("Badge Files")
+                .size(HEADING_SIZE)
+                .horizontal_alignment(iced::alignment::Horizontal::Center),
+            Space::new(Length::Shrink, Length::Fixed(10.0)),
+            path_bar,
+            container(listing)
+                .width(Length::Fixed(800.0))
+                .height(Length::Fill)
+                .padding(10)
+                .style(theme_fn_container(SummaryBoxStyle)),
+            actions,
+            status,
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        // Create bottom navigation buttons positioned at bottom corners
+        let bottom_navigation = container(
+            row![back_button, Space::with_width(Length::Fill)].align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(20);
+
+        column![
+            container(main_content)
+                .width(Length::Fill)
+                .height(Length::Fill),
+            bottom_navigation
+        ]
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn render_console_screen(&self) -> Element<'_, Message> {
+        let back_button = button(text("Back").size(BUTTON_TEXT_SIZE))
+            .on_press(Message::NavigateTo(self.console_return_screen))
+            .padding([10, 40])
+            .style(theme_fn(YellowButtonStyle));
+
+        let small_button = |label: &str, message: Option<Message>| {
+            let enabled = message.is_some();
+            button(text(label.to_string()).size(BODY_SIZE))
+                .on_press_maybe(message)
+                .padding([8, 20])
+                .style(if enabled {
+                    theme_fn(YellowButtonStyle)
+                } else {
+                    theme_fn(DisabledButtonStyle)
+                })
+        };
+
+        let connected = self.console_writer.is_some();
+        let connection_button = if self.console_connect_requested {
+            small_button("Disconnect", Some(Message::ConsoleDisconnect))
+        } else {
+            small_button(
+                "Connect",
+                (self.console_port.is_some() && !self.is_device_busy())
+                    .then_some(Message::ConsoleConnect),
+            )
+        };
+
+        let connection_row = row![
+            pick_list(
+                self.console_ports.clone(),
+                self.console_port.clone(),
+                Message::ConsoleSelectPort,
+            )
+            .placeholder("No serial ports found")
+            .width(Length::Fixed(360.0)),
+            small_button("Rescan", Some(Message::ConsoleRefreshPorts)),
+            connection_button,
+            checkbox("Hex view", self.console_hex_view)
+                .on_toggle(Message::ConsoleToggleHex)
+                .size(20)
+                .spacing(10),
+            small_button("Clear", Some(Message::ConsoleClear)),
+            if self.console_capture.is_some() {
+                small_button("Stop capture", Some(Message::ConsoleStopCapture))
+            } else {
+                small_button("Capture to file...", Some(Message::ConsoleStartCapture))
+            },
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let output_text = if self.console_hex_view {
+            let start = self
+                .console_buffer
+                .len()
+                .saturating_sub(CONSOLE_VISIBLE_HEX_BYTES)
+                / 16
+                * 16;
+            serial_console::hex_dump(&self.console_buffer[start..])
+        } else {
+            let full_text = String::from_utf8_lossy(&self.console_buffer);
+            let lines: Vec<&str> = full_text.lines().collect();
+            let start = lines.len().saturating_sub(CONSOLE_VISIBLE_LINES);
+            lines[start..].join("\n")
+        };
+
+        let output = container(
+            scrollable(
+                text(output_text)
+                    .size(13)
+                    .font(iced::Font::MONOSPACE)
+                    .style(iced::theme::Text::Color(Color::from_rgb(0.9, 0.9, 0.9)))
+                    .width(Length::Fill),
+            )
+            .id(scrollable::Id::new(CONSOLE_SCROLL_ID))
+            .height(Length::Fill),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(10)
+        .style(theme_fn_container(ConsoleOutputStyle));
+
+        let mut command_input =
+            text_input::<_, Theme, iced::Renderer>("Type a command and press Enter", &self.console_input)
+                .on_input(Message::ConsoleInputChanged)
+                .padding(10)
+                .size(BODY_SIZE)
+                .font(iced::Font::MONOSPACE)
+                .width(Length::Fill)
+                .id(text_input::Id::new(CONSOLE_INPUT_ID));
+        if connected {
+            command_input = command_input.on_submit(Message::ConsoleSend);
+        }
+
+        let input_row = row![
+            command_input,
+            small_button("Send", connected.then_some(Message::ConsoleSend)),
+            small_button("Ctrl+C", connected.then_some(Message::ConsoleSendInterrupt)),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let status_text = if self.is_device_busy() && self.console_connect_requested {
+            "Paused while the wizard is using the badge".to_string()
+        } else {
+            self.console_status.clone().unwrap_or_default()
+        };
+
+        // Create main content without buttons
+        let main_content = column![
+            Space::with_height(Length::Fixed(20.0)),
+            text("Serial Console")
+                .size(HEADING_SIZE)
+                .horizontal_alignment(iced::alignment::Horizontal::Center),
+            connection_row,
+            output,
+            input_row,
+            text(status_text)
+                .size(14)
+                .style(iced::theme::Text::Color(text_color())),
+        ]
+        .spacing(10)
+        .padding([0, 40])
+        .align_items(Alignment::Center)
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        // Create bottom navigation buttons positioned at bottom corners
+        let bottom_navigation = container(
+            row![back_button, Space::with_width(Length::Fill)].align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(20);
+
+        column![
+            container(main_content)
+                .width(Length::Fill)
+                .height(Length::Fill),
+            bottom_navigation
+        ]
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn render_emulator_screen(&self) -> Element<'_, Message> {
+        let back_button = button(text("Back").size(BUTTON_TEXT_SIZE))
+            .on_press(Message::NavigateTo(AppScreen::Summary))
+            .padding([10, 40])
+            .style(theme_fn(YellowButtonStyle));
+
+        let small_button = |label: &str, message: Option<Message>| {
+            let enabled = message.is_some();
+            button(text(label.to_string()).size(BODY_SIZE))
+                .on_press_maybe(message)
+                .padding([8, 20])
+                .style(if enabled {
+                    theme_fn(YellowButtonStyle)
+                } else {
+                    theme_fn(DisabledButtonStyle)
+                })
+        };
+
+        let led_mode = self
+            .emulator_screen
+            .led_mode
+            .and_then(|mode| u8::try_from(mode).ok())
+            .and_then(LedMode::from_integer);
+        let badge = canvas(emulator::BadgeView {
+            screen: &self.emulator_screen,
+            display_cache: &self.emulator_display_cache,
+            leds: emulator::led_colors(led_mode, self.emulator_led_elapsed),
+        })
+        .width(Length::Fixed(emulator::VIEW_WIDTH))
+        .height(Length::Fixed(emulator::VIEW_HEIGHT));
+
+        let badge_buttons = BadgeButton::ALL.iter().fold(
+            row!().spacing(10).align_items(Alignment::Center),
+            |row_acc, badge_button| {
+                row_acc.push(small_button(
+                    badge_button.display_name(),
+                    self.emulator_input
+                        .is_some()
+                        .then_some(Message::EmulatorButton(*badge_button)),
+                ))
+            },
+        );
+
+        let controls = row![
+            small_button("Restart", Some(Message::RunEmulator)),
+            small_button("Stop", self.emulator_running.then_some(Message::EmulatorStop)),
+        ]
+        .spacing(10);
+
+        let status = match &self.emulator_status {
+            _ if self.emulator_running => text(format!(
+                "Running {} - LED mode: {}",
+                self.wasm_app().name,
+                led_mode.map_or("none", |mode| mode.display_name())
+            ))
+            .size(14)
+            .style(iced::theme::Text::Color(text_color())),
+            Some(Ok(message)) => text(message)
+                .size(14)
+                .style(iced::theme::Text::Color(text_color())),
+            Some(Err(error)) => text(error)
+                .size(14)
+                .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+            None => text(""),
+        };
+
+        let output = container(
+            scrollable(
+                text(self.emulator_output.join("\n"))
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(iced::theme::Text::Color(Color::from_rgb(0.9, 0.9, 0.9)))
+                    .width(Length::Fill),
+            )
+            .id(scrollable::Id::new(EMULATOR_SCROLL_ID))
+            .height(Length::Fill),
+        )
+        .width(Length::Fixed(emulator::VIEW_WIDTH))
+        .height(Length::Fixed(140.0))
+        .padding(10)
+        .style(theme_fn_container(ConsoleOutputStyle));
+
+        // Create main content without buttons
+        let main_content = column![
+            Space::with_height(Length::Fixed(20.0)),
+            text("Badge Emulator")
+                .size(HEADING_SIZE)
+                .horizontal_alignment(iced::alignment::Horizontal::Center),
+            badge,
+            badge_buttons,
+            controls,
+            status,
+            output,
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .width(Length::Fill);
+
+        // Create bottom navigation buttons positioned at bottom corners
+        let bottom_navigation = container(
+            row![back_button, Space::with_width(Length::Fill)].align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(20);
+
+        column![
+            container(main_content)
+                .width(Length::Fill)
+                .height(Length::Fill),
+            bottom_navigation
+        ]
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn render_advanced_settings_screen(&self) -> Element<'_, Message> {
+        let back_button = button(text("Back").size(BUTTON_TEXT_SIZE))
+            .on_press(Message::NavigateTo(AppScreen::Summary))
+            .padding([10, 40])
+            .style(theme_fn(YellowButtonStyle));
+
+        let reset_button = button(text("Reset").size(BUTTON_TEXT_SIZE))
+            .on_press(Message::SelectSettingsPreset(SettingsPreset::Default))
+            .padding([10, 40])
+            .style(theme_fn(YellowButtonStyle));
+
+        let selected_preset = self.device_settings.matching_preset();
+        let presets_column = SettingsPreset::ALL.iter().fold(
+            column![text("Presets").size(BODY_SIZE + 2)]
+                .spacing(18)
+                .align_items(Alignment::Start),
+            |col_acc, preset| {
+                col_acc.push(
+                    radio(
+                        preset.display_name(),
+                        *preset,
+                        selected_preset,
+                        Message::SelectSettingsPreset,
+                    )
+                    .size(20)
+                    .spacing(10),
+                )
+            },
+        );
+
+        let flags_column = SettingsFlag::ALL.iter().fold(
+            column![text("Features").size(BODY_SIZE + 2)]
+                .spacing(18)
+                .align_items(Alignment::Start),
+            |col_acc, flag| {
+                let flag = *flag;
+                col_acc.push(
+                    checkbox(
+                        format!("{} ({})", flag.display_name(), flag.key()),
+                        self.device_settings.flag(flag),
+                    )
+                    .on_toggle(move |value| Message::ToggleSetting(flag, value))
+                    .size(20)
+                    .spacing(10),
+                )
+            },
+        );
 
-        let selected_led_text = match &self.selected_led_mode {
-            Some(mode) => mode.display_name(),
-            None => "No LED Pattern Selected",
+        let app_script_path = self.wasm_app().remote_path();
+        let labeled_input = |label: &str, placeholder: &str, value: &str, on_input: fn(String) -> Message| {
+            column![
+                text(label.to_string()).size(BODY_SIZE),
+                text_input::<_, Theme, iced::Renderer>(placeholder, value)
+                    .on_input(on_input)
+                    .padding(10)
+                    .size(BODY_SIZE)
+                    .width(Length::Fixed(360.0)),
+            ]
+            .spacing(5)
         };
 
-        let badge_name_text = if self.badge_name.is_empty() {
-            "No Name Entered"
+        let default_name = self.device_settings.effective_ssid(&self.badge_name);
+        let names_column = column![
+            text("Names and Paths").size(BODY_SIZE + 2),
+            labeled_input(
+                "Wi-Fi name (wifiAPssid)",
+                &default_name,
+                &self.device_settings.wifi_ap_ssid,
+                Message::WifiSsidChanged,
+            ),
+            row![
+                labeled_input(
+                    "Wi-Fi passphrase (wifiAPpass)",
+                    "Required when authentication is on",
+                    &self.device_settings.wifi_ap_passphrase,
+                    Message::WifiPassphraseChanged,
+                ),
+                button(text("Generate").size(BODY_SIZE))
+                    .on_press(Message::GenerateWifiPassphrase)
+                    .padding([8, 20])
+                    .style(theme_fn(YellowButtonStyle)),
+            ]
+            .spacing(10)
+            .align_items(Alignment::End),
+            labeled_input(
+                "Bluetooth name (btAPen)",
+                &default_name,
+                &self.device_settings.bluetooth_name,
+                Message::BluetoothNameChanged,
+            ),
+            labeled_input(
+                "Default script (defscript)",
+                &app_script_path,
+                &self.device_settings.default_script,
+                Message::DefaultScriptChanged,
+            ),
+        ]
+        .spacing(18)
+        .align_items(Alignment::Start);
+
+        let errors = self.device_settings.validate(&self.badge_name);
+        let validation_display = if errors.is_empty() {
+            container(
+                text("Settings look good")
+                    .size(16)
+                    .style(iced::theme::Text::Color(text_color())),
+            )
         } else {
-            &self.badge_name
+            container(
+                errors
+                    .iter()
+                    .fold(column![].spacing(5), |col_acc, error| {
+                        col_acc.push(
+                            text(error)
+                                .size(14)
+                                .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
+                        )
+                    }),
+            )
+            .padding(10)
+            .style(theme_fn_container(ErrorBoxStyle))
         };
 
         // Create main content without buttons
         let main_content = column![
-            Space::with_height(Length::Fixed(15.0)),
-            text("Configuration Summary")
+            Space::with_height(Length::Fixed(30.0)),
+            text("Advanced Settings")
                 .size(HEADING_SIZE)
                 .horizontal_alignment(iced::alignment::Horizontal::Center),
+            text("These values are written to settings.txt on the badge")
+                .size(16)
+                .style(iced::theme::Text::Color(Color::from_rgb8(100, 100, 100))),
             Space::new(Length::Shrink, Length::Fixed(30.0)),
             container(
-                column![
-                    // Badge Image Section
-                    column![
-                        text("Badge Image:")
-                            .size(BODY_SIZE + 2)
-                            .horizontal_alignment(iced::alignment::Horizontal::Center),
-                        Space::new(Length::Shrink, Length::Fixed(10.0)),
-                        container(selected_image_display)
-                            .width(Length::Fill)
-                            .center_x(),
-                    ]
-                    .align_items(Alignment::Center)
-                    .spacing(5),
-                    Space::new(Length::Shrink, Length::Fixed(25.0)),
-                    // LED Pattern and Badge Name Section
-                    column![
-                        row![
-                            text("LED Pattern: ").size(BODY_SIZE + 2),
-                            text(selected_led_text)
-                                .size(BODY_SIZE + 2)
-                                .style(iced::theme::Text::Color(*BLUE_TEXT)),
-                        ]
-                        .spacing(10)
-                        .align_items(Alignment::Center),
-                        Space::new(Length::Shrink, Length::Fixed(15.0)),
-                        row![
-                            text("Badge Name: ").size(BODY_SIZE + 2),
-                            text(badge_name_text)
-                                .size(BODY_SIZE + 2)
-                                .style(iced::theme::Text::Color(*BLUE_TEXT)),
-                        ]
-                        .spacing(10)
-                        .align_items(Alignment::Center),
-                    ]
-                    .align_items(Alignment::Center)
-                ]
-                .align_items(Alignment::Center)
+                row![presets_column, flags_column, names_column]
+                    .spacing(60)
+                    .align_items(Alignment::Start),
             )
             .padding(30)
             .style(theme_fn_container(SummaryBoxStyle)),
-            Space::new(Length::Shrink, Length::Fixed(40.0)),
-            // Configuration section
-            column![
-                text("Device Configuration")
-                    .size(HEADING_SIZE - 5)
-                    .horizontal_alignment(iced::alignment::Horizontal::Center),
-                Space::new(Length::Shrink, Length::Fixed(20.0)),
-                configure_button,
-                Space::new(Length::Shrink, Length::Fixed(20.0)),
-                if self.is_configuring || self.configuration_progress > 0.0 {
-                    let status_text = if self.is_configuring {
-                        &self.configuration_status
-                    } else if self.configuration_error.is_some() {
-                        "Configuration failed - see details below"
-                    } else if self.configuration_progress >= 1.0 {
-                        "Configuration Complete! You can configure again anytime."
-                    } else {
-                        &format!("{}%", (self.configuration_progress * 100.0) as u32)
-                    };
-
-                    let mut status_column = column![
-                        progress_bar(0.0..=1.0, self.configuration_progress)
-                            .width(Length::Fixed(400.0))
-                            .height(Length::Fixed(20.0)),
-                        Space::new(Length::Shrink, Length::Fixed(10.0)),
-                        text(status_text)
-                            .size(16)
-                            .horizontal_alignment(iced::alignment::Horizontal::Center)
-                    ];
-
-                    // Add console output and error display side by side if there's any
-                    let has_console = !self.configuration_console_output.is_empty();
-                    let has_error = self.configuration_error.is_some();
-                    
-                    if has_console || has_error {
-                        status_column = status_column.push(Space::new(Length::Shrink, Length::Fixed(15.0)));
-                        
-                        // Create a horizontal row for console and error
-                        let mut console_row = row!().spacing(20).align_items(Alignment::Start);
-                        
-                        // Add console output if present
-                        if has_console {
-                            let console_column = column![
-                                text("Console Output:")
-                                    .size(14)
-                                    .style(iced::theme::Text::Color(*BLUE_TEXT)),
-                                Space::new(Length::Shrink, Length::Fixed(5.0)),
-                                container(
-                                    text(&self.configuration_console_output)
-                                        .size(12)
-                                        .style(iced::theme::Text::Color(Color::from_rgb(0.9, 0.9, 0.9)))
-                                        .horizontal_alignment(iced::alignment::Horizontal::Left)
-                                )
-                                .width(Length::Fixed(if has_error { 480.0 } else { 800.0 }))
-                                .height(Length::Fixed(250.0))
-                                .padding(10)
-                                .style(theme_fn_container(ConsoleOutputStyle))
-                            ]
-                            .spacing(5);
-                            
-                            console_row = console_row.push(console_column);
-                        }
-                        
-                        // Add error message if present
-                        if let Some(error) = &self.configuration_error {
-                            let error_column = column![
-                                text("Error:")
-                                    .size(14)
-                                    .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0))),
-                                Space::new(Length::Shrink, Length::Fixed(5.0)),
-                                container(
-                                    text(error)
-                                        .size(14)
-                                        .style(iced::theme::Text::Color(Color::from_rgb8(200, 0, 0)))
-                                        .horizontal_alignment(iced::alignment::Horizontal::Left),
-                                )
-                                .width(Length::Fixed(if has_console { 480.0 } else { 800.0 }))
-                                .height(Length::Fixed(250.0))
-                                .padding(10)
-                                .style(theme_fn_container(ErrorBoxStyle))
-                            ]
-                            .spacing(5);
-                            
-                            console_row = console_row.push(error_column);
-                        }
-                        
-                        status_column = status_column.push(console_row);
-                    }
-
-                    container(status_column.align_items(Alignment::Center))
-                        .width(Length::Fill)
-                        .center_x()
-                } else {
-                    container(Space::new(Length::Shrink, Length::Fixed(50.0)))
-                }
-            ]
-            .align_items(Alignment::Center)
+            Space::new(Length::Shrink, Length::Fixed(20.0)),
+            validation_display,
         ]
         .spacing(10)
         .align_items(Alignment::Center)
@@ -1028,7 +4360,7 @@ impl BuildABadgeApp {
 
         // Create bottom navigation buttons positioned at bottom corners
         let bottom_navigation = container(
-            row![back_button, Space::with_width(Length::Fill), done_button,]
+            row![back_button, Space::with_width(Length::Fill), reset_button,]
                 .align_items(Alignment::Center),
         )
         .width(Length::Fill)
@@ -1052,7 +4384,7 @@ impl ButtonStyleSheet for YellowButtonStyle {
     type Style = Theme;
     fn active(&self, _style: &Self::Style) -> ButtonAppearance {
         ButtonAppearance {
-            background: Some((*YELLOW).into()),
+            background: Some(accent_color().into()),
             text_color: Color::BLACK,
             border: Border {
                 radius: 4.0.into(),
@@ -1064,7 +4396,7 @@ impl ButtonStyleSheet for YellowButtonStyle {
     fn hovered(&self, style: &Self::Style) -> ButtonAppearance {
         let active = self.active(style);
         ButtonAppearance {
-            background: Some(Color { a: 0.8, ..*YELLOW }.into()),
+            background: Some(Color { a: 0.8, ..accent_color() }.into()),
             ..active
         }
     }
@@ -1115,10 +4447,10 @@ impl ButtonStyleSheet for SelectedBadgeStyle {
     type Style = Theme;
     fn active(&self, _style: &Self::Style) -> ButtonAppearance {
         ButtonAppearance {
-            background: Some(Color { a: 0.3, ..*YELLOW }.into()),
+            background: Some(Color { a: 0.3, ..accent_color() }.into()),
             text_color: Color::BLACK,
             border: Border {
-                color: *YELLOW,
+                color: accent_color(),
                 width: 2.0,
                 radius: 8.0.into(),
             },
@@ -1211,428 +4543,95 @@ where
     iced::theme::Container::Custom(Box::new(style))
 }
 
-fn configuration_subscription(
-    selected_image: Option<image::Handle>,
-    selected_led_mode: Option<LedMode>,
-    badge_name: String,
-) -> Subscription<Message> {
+/// Icon, failed step, fix and fwi-serial's own words for a failed configuration run.
+fn render_config_error(failure: &ConfigFailure) -> Element<'_, Message> {
+    let error = &failure.error;
+    let error_red = Color::from_rgb8(200, 0, 0);
+    row![
+        text(error.icon()).size(32),
+        column![
+            text(error.title())
+                .size(16)
+                .style(iced::theme::Text::Color(error_red)),
+            text(format!("{} failed", failure.step)).size(14),
+            text(error.remediation()).size(14),
+            Space::new(Length::Shrink, Length::Fixed(5.0)),
+            text(error.detail())
+                .size(12)
+                .style(iced::theme::Text::Color(Color::from_rgb8(120, 60, 60))),
+        ]
+        .spacing(5),
+    ]
+    .spacing(12)
+    .align_items(Alignment::Start)
+    .into()
+}
+
+fn configuration_subscription(job: ConfigJob) -> Subscription<Message> {
     iced::subscription::unfold(
         std::any::TypeId::of::<ConfigurationState>(),
         ConfigurationState::Start,
         move |state| {
-            let selected_image = selected_image.clone();
-            let selected_led_mode = selected_led_mode;
-            let badge_name = badge_name.clone();
-            
+            let job = job.clone();
+
             async move {
                 match state {
                     ConfigurationState::Start => {
-                        // Step 1: Create configuration files
                         println!("Configuration: Starting configuration process");
-                        tokio::time::sleep(Duration::from_millis(100)).await;
-                        
-                        let config_content = create_config_content(selected_led_mode, badge_name.clone());
-                        let config_file = "build_a_badge.txt";
-                        println!("Configuration: Creating config file '{}' with content:\n{}", config_file, config_content);
-                        if fs::write(config_file, &config_content).is_err() {
-                            let error_msg = format!("Failed to write configuration file: {}", config_file);
-                            println!("Configuration ERROR: {}", error_msg);
-                            return (
-                                Message::ConfigurationComplete(Err(error_msg)),
-                                ConfigurationState::Done,
-                            );
-                        }
-                        println!("Configuration: Successfully wrote config file");
-
-                        let settings_content = create_settings_content(badge_name.clone());
-                        let settings_file = "settings.txt";
-                        println!("Configuration: Creating settings file '{}' with content:\n{}", settings_file, settings_content);
-                        if fs::write(settings_file, settings_content).is_err() {
-                            let error_msg = format!("Failed to write settings file: {}", settings_file);
-                            println!("Configuration ERROR: {}", error_msg);
-                            return (
-                                Message::ConfigurationComplete(Err(error_msg)),
-                                ConfigurationState::Done,
-                            );
-                        }
-                        println!("Configuration: Successfully wrote settings file");
-
-                        let step_message = "Step 1: Uploading configuration file...";
-                        let detailed_message = format!("Generated configuration file content:\n{}\nStep 1: Uploading configuration file...", config_content);
-                        println!("Configuration: {}", detailed_message);
-                        (
-                            Message::ConfigurationStepUpdate(step_message.to_string(), 0.1),
-                            ConfigurationState::UploadConfig,
-                        )
-                    }
-                    ConfigurationState::UploadConfig => {
-                        // Step 1: Upload configuration file
-                        println!("Configuration: Starting upload of configuration file");
-                        
-                        // Add timeout to prevent hanging
-                        let result = tokio::time::timeout(
-                            Duration::from_secs(30), // 10 second timeout
-                            tokio::process::Command::new("fwi-serial")
-                                .current_dir(".")
-                                .arg("-s")
-                                .arg("build_a_badge.txt")
-                                .arg("-fn")
-                                .arg("/build_a_badge.txt")
-                                .arg("-mi")
-                                .arg("1")
-                                .output()
-                        ).await;
-
-                        let (success, message) = match result {
-                            Ok(Ok(output)) => {
-                                let console_output = format!("Configuration: fwi-serial command completed with exit status: {}", output.status);
-                                println!("{}", console_output);
-                                
-                                let mut full_output = console_output;
-                                
-                                if !output.stdout.is_empty() {
-                                    let stdout_output = format!("Configuration: stdout: {}", String::from_utf8_lossy(&output.stdout));
-                                    println!("{}", stdout_output);
-                                    full_output.push_str(&format!("\n{}", stdout_output));
-                                }
-                                if !output.stderr.is_empty() {
-                                    let stderr_output = format!("Configuration: stderr: {}", String::from_utf8_lossy(&output.stderr));
-                                    println!("{}", stderr_output);
-                                    full_output.push_str(&format!("\n{}", stderr_output));
-                                }
-                                
-                                if output.status.success() {
-                                    let success_msg = "✓ Configuration file uploaded successfully\nStep 2: Uploading image file...".to_string();
-                                    let final_msg = format!("{}\nConfiguration: {}", full_output, success_msg);
-                                    println!("Configuration: {}", success_msg);
-                                    (true, final_msg)
-                                } else {
-                                    let stderr = String::from_utf8_lossy(&output.stderr);
-                                    let error_msg = format!("✗ Configuration upload failed: {}\nConfiguration stopped due to error.", stderr);
-                                    let final_msg = format!("{}\nConfiguration ERROR: {}", full_output, error_msg);
-                                    println!("Configuration ERROR: {}", error_msg);
-                                    (false, final_msg)
-                                }
-                            }
-                            Ok(Err(e)) => {
-                                let msg = format!("✗ Configuration upload error: {}\nConfiguration stopped due to error.", e);
-                                println!("Configuration ERROR: {}", msg);
-                                (false, msg)
-                            },
-                            Err(_) => {
-                                let msg = "✗ Configuration upload timed out (10 seconds) - device may not be connected\nConfiguration stopped due to error.".to_string();
-                                println!("Configuration ERROR: {}", msg);
-                                println!("Configuration: Timeout occurred, returning error message");
-                                (false, msg)
-                            }
-                        };
-
-                        if !success {
-                            println!("Configuration: Returning error ConfigurationComplete with message: {}", message);
-                            return (
-                                Message::ConfigurationComplete(Err(message.clone())),
-                                ConfigurationState::Done,
-                            );
-                        }
-
-                        (
-                            Message::ConfigurationStepUpdate("Step 2: Uploading image file...".to_string(), 0.3),
-                            ConfigurationState::UploadImage,
-                        )
-                    }
-                    ConfigurationState::UploadImage => {
-                        // Step 2: Upload image file
-                        println!("Configuration: Starting upload of image file");
-                        
-                        // Determine the correct image file path based on selection
-                        let image_file_path = match selected_image.as_ref() {
-                            Some(handle) => {
-                                // Map the image handle to the corresponding .fwi file
-                                if handle == &*DEFCON_LOGO_IMAGE {
-                                    "assets/defcon_logo.fwi"
-                                } else if handle == &*DOGE_IMAGE {
-                                    "assets/doge.fwi"
-                                } else if handle == &*PUPPY_IMAGE {
-                                    "assets/puppy.fwi"
-                                } else if handle == &*PIP_BOY_IMAGE {
-                                    "assets/pip_boy.fwi"
-                                } else if handle == &*VEGAS_IMAGE {
-                                    "assets/vegas.fwi"
-                                } else {
-                                    "assets/badge_placeholder.fwi"
-                                }
-                            }
-                            None => "assets/badge_placeholder.fwi"
-                        };
-                        
-                        println!("Configuration: Using image file: {}", image_file_path);
-                        
-                        let result = tokio::time::timeout(
-                            Duration::from_secs(30), // 10 second timeout
-                            tokio::process::Command::new("fwi-serial")
-                                .current_dir(".")
-                                .arg("-s")
-                                .arg(image_file_path)
-                                .arg("-fn")
-                                .arg("/images/build_a_badge.fwi")
-                                .output()
-                        ).await;
-
-                        let (success, message) = match result {
-                            Ok(Ok(output)) => {
-                                let console_output = format!("Configuration: fwi-serial image upload completed with exit status: {}", output.status);
-                                println!("{}", console_output);
-                                
-                                let mut full_output = console_output;
-                                
-                                if !output.stdout.is_empty() {
-                                    let stdout_output = format!("Configuration: stdout: {}", String::from_utf8_lossy(&output.stdout));
-                                    println!("{}", stdout_output);
-                                    full_output.push_str(&format!("\n{}", stdout_output));
-                                }
-                                if !output.stderr.is_empty() {
-                                    let stderr_output = format!("Configuration: stderr: {}", String::from_utf8_lossy(&output.stderr));
-                                    println!("{}", stderr_output);
-                                    full_output.push_str(&format!("\n{}", stderr_output));
-                                }
-                                
-                                if output.status.success() {
-                                    let success_msg = "✓ Image file uploaded successfully\nStep 3: Uploading WASM file...".to_string();
-                                    let final_msg = format!("{}\nConfiguration: {}", full_output, success_msg);
-                                    println!("Configuration: {}", success_msg);
-                                    (true, final_msg)
-                                } else {
-                                    let stderr = String::from_utf8_lossy(&output.stderr);
-                                    let error_msg = format!("✗ Image upload failed: {}\nConfiguration stopped due to error.", stderr);
-                                    let final_msg = format!("{}\nConfiguration ERROR: {}", full_output, error_msg);
-                                    println!("Configuration ERROR: {}", error_msg);
-                                    (false, final_msg)
-                                }
-                            }
-                            Ok(Err(e)) => {
-                                let msg = format!("✗ Image upload error: {}\nConfiguration stopped due to error.", e);
-                                println!("Configuration ERROR: {}", msg);
-                                (false, msg)
-                            },
-                            Err(_) => {
-                                let msg = "✗ Image upload timed out (10 seconds) - device may not be connected\nConfiguration stopped due to error.".to_string();
-                                println!("Configuration ERROR: {}", msg);
-                                (false, msg)
-                            }
-                        };
-
-                        if !success {
-                            return (
-                                Message::ConfigurationComplete(Err(message.clone())),
-                                ConfigurationState::Done,
-                            );
-                        }
-
+                        let steps = Arc::new(pipeline::plan(&job));
+                        let first = format!("Step 1/{}: {}...", steps.len(), steps[0].name);
                         (
-                            Message::ConfigurationStepUpdate("Step 3: Uploading WASM file...".to_string(), 0.5),
-                            ConfigurationState::UploadWasm,
+                            Message::ConfigurationStepUpdate(first, 0.0),
+                            ConfigurationState::Running(steps, 0),
                         )
                     }
-                    ConfigurationState::UploadWasm => {
-                        // Step 3: Upload WASM file (allowed to fail)
-                        println!("Configuration: Starting upload of WASM file (expected to fail if file doesn't exist)");
-                        
-                        let result = tokio::time::timeout(
-                            Duration::from_secs(30), // 10 second timeout
-                            tokio::process::Command::new("fwi-serial")
-                                .current_dir(".")
-                                .arg("-s")
-                                .arg("build_a_badge.wasm")
-                                .output()
-                        ).await;
-
-                        let _message = match result {
-                            Ok(Ok(output)) => {
-                                let console_output = format!("Configuration: fwi-serial WASM upload completed with exit status: {}", output.status);
-                                println!("{}", console_output);
-                                
-                                let mut full_output = console_output;
-                                
-                                if !output.stdout.is_empty() {
-                                    let stdout_output = format!("Configuration: stdout: {}", String::from_utf8_lossy(&output.stdout));
-                                    println!("{}", stdout_output);
-                                    full_output.push_str(&format!("\n{}", stdout_output));
-                                }
-                                if !output.stderr.is_empty() {
-                                    let stderr_output = format!("Configuration: stderr: {}", String::from_utf8_lossy(&output.stderr));
-                                    println!("{}", stderr_output);
-                                    full_output.push_str(&format!("\n{}", stderr_output));
-                                }
-                                
-                                if output.status.success() {
-                                    let msg = "✓ WASM file uploaded successfully\nStep 4: Uploading settings file...".to_string();
-                                    let final_msg = format!("{}\nConfiguration: {}", full_output, msg);
-                                    println!("Configuration: {}", msg);
-                                    final_msg
-                                } else {
-                                    let msg = "✗ WASM upload failed (expected - file doesn't exist yet)\nStep 4: Uploading settings file...".to_string();
-                                    let final_msg = format!("{}\nConfiguration: {}", full_output, msg);
-                                    println!("Configuration: {}", msg);
-                                    final_msg
-                                }
-                            }
-                            Ok(Err(e)) => {
-                                let msg = "✗ WASM upload error (expected - file doesn't exist yet)\nStep 4: Uploading settings file...".to_string();
-                                println!("Configuration: {} (Error: {})", msg, e);
-                                msg
-                            },
-                            Err(_) => {
-                                let msg = "✗ WASM upload timed out (expected - file doesn't exist yet)\nStep 4: Uploading settings file...".to_string();
-                                println!("Configuration: {}", msg);
-                                msg
-                            }
-                        };
+                    ConfigurationState::Running(steps, index) => {
+                        let step = &steps[index];
+                        println!("Configuration: {}", step.name);
 
-                        // WASM failure is expected and doesn't stop the process
-                        (
-                            Message::ConfigurationStepUpdate("Step 4: Uploading settings file...".to_string(), 0.7),
-                            ConfigurationState::UploadSettings,
-                        )
-                    }
-                    ConfigurationState::UploadSettings => {
-                        // Step 4: Upload settings file
-                        println!("Configuration: Starting upload of settings file");
-                        
-                        let result = tokio::time::timeout(
-                            Duration::from_secs(30), // 10 second timeout
-                            tokio::process::Command::new("fwi-serial")
-                                .current_dir(".")
-                                .arg("-s")
-                                .arg("settings.txt")
-                                .arg("-fn")
-                                .arg("/settings.txt")
-                                .arg("-mi")
-                                .arg("1")
-                                .output()
-                        ).await;
-
-                        let (success, final_message) = match result {
-                            Ok(Ok(output)) => {
-                                let console_output = format!("Configuration: fwi-serial settings upload completed with exit status: {}", output.status);
-                                println!("{}", console_output);
-                                
-                                let mut full_output = console_output;
-                                
-                                if !output.stdout.is_empty() {
-                                    let stdout_output = format!("Configuration: stdout: {}", String::from_utf8_lossy(&output.stdout));
-                                    println!("{}", stdout_output);
-                                    full_output.push_str(&format!("\n{}", stdout_output));
-                                }
-                                if !output.stderr.is_empty() {
-                                    let stderr_output = format!("Configuration: stderr: {}", String::from_utf8_lossy(&output.stderr));
-                                    println!("{}", stderr_output);
-                                    full_output.push_str(&format!("\n{}", stderr_output));
-                                }
-                                
-                                if output.status.success() {
-                                    let success_msg = "✓ Settings file uploaded successfully".to_string();
-                                    let final_msg = format!("{}\nConfiguration: {}", full_output, success_msg);
-                                    println!("Configuration: {}", success_msg);
-                                    (true, final_msg)
-                                } else {
-                                    let stderr = String::from_utf8_lossy(&output.stderr);
-                                    let error_msg = format!("✗ Settings upload failed: {}\nConfiguration stopped due to error.", stderr);
-                                    let final_msg = format!("{}\nConfiguration ERROR: {}", full_output, error_msg);
-                                    println!("Configuration ERROR: {}", error_msg);
-                                    (false, final_msg)
-                                }
+                        let report = match pipeline::run_step(step).await {
+                            Ok(report) => format!("✓ {}", report),
+                            // Optional steps (a backup of a blank badge, say) never stop the run
+                            Err(error) if step.optional => {
+                                format!("⚠ {} skipped: {}", step.name, error)
                             }
-                            Ok(Err(e)) => {
-                                let msg = format!("✗ Settings upload error: {}\nConfiguration stopped due to error.", e);
-                                println!("Configuration ERROR: {}", msg);
-                                (false, msg)
-                            },
-                            Err(_) => {
-                                let msg = "✗ Settings upload timed out (10 seconds) - device may not be connected\nConfiguration stopped due to error.".to_string();
-                                println!("Configuration ERROR: {}", msg);
-                                (false, msg)
+                            Err(error) => {
+                                let failure = ConfigFailure {
+                                    step: step.name,
+                                    error,
+                                };
+                                println!("Configuration ERROR: {}", failure);
+                                return (
+                                    Message::ConfigurationComplete(Err(failure)),
+                                    ConfigurationState::Done,
+                                );
                             }
                         };
+                        println!("Configuration: {}", report);
 
-                        if !success {
+                        let next = index + 1;
+                        if next == steps.len() {
+                            println!("Configuration: All steps completed successfully!");
                             return (
-                                Message::ConfigurationComplete(Err(final_message)),
+                                Message::ConfigurationComplete(Ok(format!(
+                                    "{}\nConfiguration completed successfully!",
+                                    report
+                                ))),
                                 ConfigurationState::Done,
                             );
                         }
 
+                        let update = format!(
+                            "{}\nStep {}/{}: {}...",
+                            report,
+                            next + 1,
+                            steps.len(),
+                            steps[next].name
+                        );
+                        let progress = pipeline::progress_at(&steps, next);
                         (
-                            Message::ConfigurationStepUpdate("Step 5: Running WASM application...".to_string(), 0.9),
-                            ConfigurationState::RunWasm,
-                        )
-                    }
-                    ConfigurationState::RunWasm => {
-                        // Step 5: Run WASM application
-                        println!("Configuration: Starting WASM application execution");
-                        
-                        let result = tokio::time::timeout(
-                            Duration::from_secs(30),
-                            tokio::process::Command::new("fwi-serial")
-                                .current_dir(".")
-                                .arg("-w")
-                                .arg("build_a_badge.wasm")
-                                .output()
-                        ).await;
-
-                        let (success, final_message) = match result {
-                            Ok(Ok(output)) => {
-                                let console_output = format!("Configuration: fwi-serial WASM run completed with exit status: {}", output.status);
-                                println!("{}", console_output);
-                                
-                                let mut full_output = console_output;
-                                
-                                if !output.stdout.is_empty() {
-                                    let stdout_output = format!("Configuration: stdout: {}", String::from_utf8_lossy(&output.stdout));
-                                    println!("{}", stdout_output);
-                                    full_output.push_str(&format!("\n{}", stdout_output));
-                                }
-                                if !output.stderr.is_empty() {
-                                    let stderr_output = format!("Configuration: stderr: {}", String::from_utf8_lossy(&output.stderr));
-                                    println!("{}", stderr_output);
-                                    full_output.push_str(&format!("\n{}", stderr_output));
-                                }
-                                
-                                if output.status.success() {
-                                    let success_msg = "✓ WASM application executed successfully".to_string();
-                                    let final_msg = format!("{}\nConfiguration: {}", full_output, success_msg);
-                                    println!("Configuration: {}", success_msg);
-                                    (true, final_msg)
-                                } else {
-                                    let stderr = String::from_utf8_lossy(&output.stderr);
-                                    let error_msg = format!("✗ WASM execution failed: {}\nConfiguration stopped due to error.", stderr);
-                                    let final_msg = format!("{}\nConfiguration ERROR: {}", full_output, error_msg);
-                                    println!("Configuration ERROR: {}", error_msg);
-                                    (false, final_msg)
-                                }
-                            }
-                            Ok(Err(e)) => {
-                                let msg = format!("✗ WASM execution error: {}\nConfiguration stopped due to error.", e);
-                                println!("Configuration ERROR: {}", msg);
-                                (false, msg)
-                            },
-                            Err(_) => {
-                                let msg = "✗ WASM execution timed out (30 seconds) - device may not be connected\nConfiguration stopped due to error.".to_string();
-                                println!("Configuration ERROR: {}", msg);
-                                (false, msg)
-                            }
-                        };
-
-                        let result = if success {
-                            println!("Configuration: All steps completed successfully!");
-                            Ok("Configuration completed successfully!".to_string())
-                        } else {
-                            println!("Configuration: Process failed during WASM execution");
-                            Err(final_message)
-                        };
-
-                        (
-                            Message::ConfigurationComplete(result),
-                            ConfigurationState::Done,
+                            Message::ConfigurationStepUpdate(update, progress),
+                            ConfigurationState::Running(steps, next),
                         )
                     }
                     ConfigurationState::Done => {
@@ -1646,42 +4645,9 @@ fn configuration_subscription(
     )
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum ConfigurationState {
     Start,
-    UploadConfig,
-    UploadImage,
-    UploadWasm,
-    UploadSettings,
-    RunWasm,
+    /// The plan, and the step about to run.
+    Running(Arc<Vec<ConfigStep>>, usize),
     Done,
 }
-
-fn create_config_content(selected_led_mode: Option<LedMode>, badge_name: String) -> String {
-    let led_pattern = match selected_led_mode {
-        Some(mode) => mode.as_integer().to_string(),
-        None => "0".to_string(), // Default to Manual (0)
-    };
-
-    let name = if badge_name.is_empty() {
-        "Boring"
-    } else {
-        &badge_name
-    };
-
-    format!("{name}-WiLi\n{led_pattern}\n")
-}
-
-fn create_settings_content(badge_name: String) -> String {
-    format!(
-        "wifiAPEn=1
-wifiAPssid={badge_name}-WiLi
-wifiAPAuth=0
-btEn=1
-btAPen={badge_name}-WiLi
-btTerm=1
-serverStart=1
-orcaBottlenoseComm=1
-defscript=/scripts/build_a_badge.wasm\n"
-    )
-}
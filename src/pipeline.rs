@@ -0,0 +1,341 @@
+// pipeline.rs
+//
+// What "Configure Device" does, as a list of steps. Each `ConfigStep` names what
+// it puts on the badge and how; `run_step` carries any of them out. Nothing here
+// depends on iced, so the same plan can be driven from the UI or a terminal.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::LedMode;
+use crate::app_config;
+use crate::backup::{self, BackupKind};
+use crate::badge_config::BadgeConfig;
+use crate::config_error::ConfigError;
+use crate::device;
+use crate::run_log;
+use crate::settings::DeviceSettings;
+use crate::wasm_apps::WasmApp;
+use crate::wasm_check;
+
+/// Everything a configuration run needs to know about the design.
+#[derive(Debug, Clone)]
+pub struct ConfigJob {
+    pub image_fwi_path: PathBuf,
+    pub led_mode: Option<LedMode>,
+    pub badge_name: String,
+    pub device_settings: DeviceSettings,
+    pub wasm_app: WasmApp,
+}
+
+/// What a step sends.
+#[derive(Debug, Clone)]
+pub enum Payload {
+    Nothing,
+    LocalFile(PathBuf),
+    /// Written to `local_path` first, which also leaves a copy for inspection.
+    Generated {
+        local_path: PathBuf,
+        contents: String,
+    },
+}
+
+impl Payload {
+    fn local_path(&self) -> Option<&PathBuf> {
+        match self {
+            Payload::Nothing => None,
+            Payload::LocalFile(path)
+            | Payload::Generated {
+                local_path: path, ..
+            } => Some(path),
+        }
+    }
+}
+
+/// How a step talks to the badge.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Saves the wizard's files, and the app at `app_remote_path`, off the badge
+    /// before anything is overwritten.
+    Backup {
+        app_remote_path: String,
+    },
+    /// Checks the payload is a WASM app the firmware can run; doesn't touch the badge.
+    CheckWasm,
+    Upload {
+        remote_path: String,
+    },
+    /// Starts an app already in `/scripts`.
+    Run {
+        file_name: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    None,
+    /// Downloads the upload again and compares it byte for byte.
+    ReadBack,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigStep {
+    /// Finishes "Step 2/7: ..." in the console.
+    pub name: &'static str,
+    pub payload: Payload,
+    pub action: Action,
+    pub verification: Verification,
+    /// Share of the progress bar, relative to the other steps.
+    pub weight: f32,
+    /// A failing optional step is reported as a warning and the run carries on.
+    pub optional: bool,
+    /// How long each fwi-serial call in the step may take.
+    pub timeout: Duration,
+}
+
+impl ConfigStep {
+    fn new(name: &'static str, payload: Payload, action: Action) -> Self {
+        Self {
+            name,
+            payload,
+            action,
+            verification: Verification::None,
+            weight: 1.0,
+            optional: false,
+            timeout: app_config::get().timeouts.command(),
+        }
+    }
+
+    fn verified(mut self) -> Self {
+        self.verification = Verification::ReadBack;
+        self
+    }
+
+    fn weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    fn timeout(mut self, seconds: u64) -> Self {
+        self.timeout = Duration::from_secs(seconds);
+        self
+    }
+}
+
+/// The steps for `job`, in the order they run.
+pub fn plan(job: &ConfigJob) -> Vec<ConfigStep> {
+    let wasm_path = job.wasm_app.local_path.clone();
+    let remote_paths = &app_config::get().remote_paths;
+    let timeouts = &app_config::get().timeouts;
+
+    vec![
+        ConfigStep::new(
+            "Backing up the badge",
+            Payload::Nothing,
+            Action::Backup {
+                app_remote_path: job.wasm_app.remote_path(),
+            },
+        )
+        .optional(),
+        ConfigStep::new(
+            "Uploading configuration file",
+            Payload::Generated {
+                local_path: PathBuf::from("build_a_badge.txt"),
+                contents: BadgeConfig::new(&job.badge_name, job.led_mode).to_file_contents(),
+            },
+            Action::Upload {
+                remote_path: remote_paths.config.clone(),
+            },
+        )
+        .timeout(timeouts.config_upload),
+        // A full-screen image is by far the largest file
+        ConfigStep::new(
+            "Uploading image file",
+            Payload::LocalFile(job.image_fwi_path.clone()),
+            Action::Upload {
+                remote_path: remote_paths.image.clone(),
+            },
+        )
+        .weight(3.0)
+        .timeout(timeouts.image_upload),
+        ConfigStep::new(
+            "Checking WASM app",
+            Payload::LocalFile(wasm_path.clone()),
+            Action::CheckWasm,
+        )
+        .weight(0.5),
+        // Verified so the badge never boots into a missing or truncated default script
+        ConfigStep::new(
+            "Uploading WASM file",
+            Payload::LocalFile(wasm_path),
+            Action::Upload {
+                remote_path: job.wasm_app.remote_path(),
+            },
+        )
+        .verified()
+        .weight(2.0)
+        .timeout(timeouts.wasm_upload),
+        ConfigStep::new(
+            "Uploading settings file",
+            Payload::Generated {
+                local_path: PathBuf::from("settings.txt"),
+                contents: job.device_settings.to_settings_txt(&job.badge_name),
+            },
+            Action::Upload {
+                remote_path: remote_paths.settings.clone(),
+            },
+        )
+        .timeout(timeouts.settings_upload),
+        ConfigStep::new(
+            "Running WASM application",
+            Payload::Nothing,
+            Action::Run {
+                file_name: job.wasm_app.file_name(),
+            },
+        )
+        .timeout(timeouts.run_app),
+    ]
+}
+
+/// Fraction of the run finished once the steps before `index` are done.
+pub fn progress_at(steps: &[ConfigStep], index: usize) -> f32 {
+    let total: f32 = steps.iter().map(|step| step.weight).sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    steps[..index.min(steps.len())]
+        .iter()
+        .map(|step| step.weight)
+        .sum::<f32>()
+        / total
+}
+
+/// Carries out one step and logs it. `Ok` holds the line reported for it.
+pub async fn run_step(step: &ConfigStep) -> Result<String, ConfigError> {
+    let started = Instant::now();
+    let result = execute(step).await;
+    run_log::record_step(step.name, started.elapsed(), &result);
+    result
+}
+
+async fn execute(step: &ConfigStep) -> Result<String, ConfigError> {
+    if let Payload::Generated {
+        local_path,
+        contents,
+    } = &step.payload
+    {
+        println!(
+            "Configuration: Writing {} with content:\n{}",
+            local_path.display(),
+            contents
+        );
+        tokio::fs::write(local_path, contents)
+            .await
+            .map_err(|e| ConfigError::io(local_path, e))?;
+    }
+
+    let local_path = step.payload.local_path();
+    let missing_payload = || ConfigError::IoWrite(format!("{} has no file to work on", step.name));
+    let report = match &step.action {
+        Action::Backup { app_remote_path } => {
+            let path = backup::backup_badge(BackupKind::PreFlash, app_remote_path.clone())
+                .await
+                .map_err(ConfigError::from_device)?;
+            format!("Pre-flash backup saved to {}", path.display())
+        }
+        Action::CheckWasm => {
+            let path = local_path.ok_or_else(missing_payload)?;
+            let bytes = tokio::fs::read(path).await.map_err(|e| {
+                ConfigError::InvalidApp(format!("Could not read {}: {}", path.display(), e))
+            })?;
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let report = wasm_check::check_module(&file_name, &bytes);
+            if !report.is_ok() {
+                return Err(ConfigError::InvalidApp(report.to_console_text()));
+            }
+            report.to_console_text()
+        }
+        Action::Upload { remote_path } => {
+            let path = local_path.ok_or_else(missing_payload)?;
+            device::upload_file(path, remote_path, step.timeout)
+                .await
+                .map_err(ConfigError::from_device)?;
+            if step.verification == Verification::ReadBack {
+                device::verify_upload(path, remote_path, step.timeout)
+                    .await
+                    .map_err(ConfigError::from_device)?;
+                format!("Uploaded {} and verified it", remote_path)
+            } else {
+                format!("Uploaded {}", remote_path)
+            }
+        }
+        Action::Run { file_name } => {
+            device::run_app(file_name, step.timeout)
+                .await
+                .map_err(ConfigError::from_device)?;
+            format!("Started {}", file_name)
+        }
+    };
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job() -> ConfigJob {
+        ConfigJob {
+            image_fwi_path: PathBuf::from("build_a_badge.fwi"),
+            led_mode: Some(LedMode::Accel),
+            badge_name: "Ada".to_string(),
+            device_settings: DeviceSettings::default(),
+            wasm_app: WasmApp::bundled().remove(0),
+        }
+    }
+
+    #[test]
+    fn progress_runs_from_zero_to_one() {
+        let steps = plan(&job());
+        assert_eq!(progress_at(&steps, 0), 0.0);
+        assert!((progress_at(&steps, steps.len()) - 1.0).abs() < 1e-6);
+        assert!(
+            (1..=steps.len())
+                .all(|index| progress_at(&steps, index) > progress_at(&steps, index - 1))
+        );
+    }
+
+    #[test]
+    fn only_the_backup_is_optional() {
+        let optional: Vec<_> = plan(&job())
+            .into_iter()
+            .filter(|step| step.optional)
+            .map(|step| step.action)
+            .collect();
+        assert!(matches!(optional.as_slice(), [Action::Backup { .. }]));
+    }
+
+    #[test]
+    fn backup_comes_before_any_upload() {
+        let steps = plan(&job());
+        let backup = steps
+            .iter()
+            .position(|step| matches!(step.action, Action::Backup { .. }))
+            .unwrap();
+        let first_upload = steps
+            .iter()
+            .position(|step| matches!(step.action, Action::Upload { .. }))
+            .unwrap();
+        assert!(backup < first_upload);
+
+        let Action::Backup { app_remote_path } = &steps[backup].action else {
+            unreachable!()
+        };
+        assert_eq!(*app_remote_path, job().wasm_app.remote_path());
+    }
+}
@@ -0,0 +1,73 @@
+// qr.rs
+//
+// Renders the Wi-Fi join QR code shown on the Summary screen when the badge's
+// access point is secured.
+
+use iced::widget::image;
+use qrcode::{Color as QrColor, QrCode};
+
+/// Pixels drawn per QR module; the quiet zone is four modules wide.
+const MODULE_SIZE: usize = 6;
+const QUIET_ZONE: usize = 4;
+
+/// Builds the `WIFI:` payload understood by phone cameras. `passphrase` is the
+/// same value written to the firmware's `wifiAPpass` key in `settings.txt`.
+pub fn wifi_join_payload(ssid: &str, passphrase: &str) -> String {
+    format!(
+        "WIFI:S:{};T:WPA;P:{};;",
+        escape_field(ssid),
+        escape_field(passphrase)
+    )
+}
+
+/// Encodes `data` as a QR code and returns it as an RGBA image handle.
+pub fn render_qr(data: &str) -> Option<image::Handle> {
+    let code = QrCode::new(data.as_bytes()).ok()?;
+    let modules = code.width();
+    let colors = code.to_colors();
+
+    let side = (modules + QUIET_ZONE * 2) * MODULE_SIZE;
+    let mut pixels = vec![255u8; side * side * 4];
+
+    for (index, color) in colors.iter().enumerate() {
+        if *color != QrColor::Dark {
+            continue;
+        }
+        let module_x = (index % modules + QUIET_ZONE) * MODULE_SIZE;
+        let module_y = (index / modules + QUIET_ZONE) * MODULE_SIZE;
+        for y in module_y..module_y + MODULE_SIZE {
+            for x in module_x..module_x + MODULE_SIZE {
+                let offset = (y * side + x) * 4;
+                pixels[offset..offset + 3].copy_from_slice(&[0, 0, 0]);
+            }
+        }
+    }
+
+    Some(image::Handle::from_pixels(side as u32, side as u32, pixels))
+}
+
+// Special characters in the WIFI: scheme are escaped with a backslash.
+fn escape_field(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_wifi_scheme_separators() {
+        assert_eq!(escape_field(r"a;b,c:d\e"), r"a\;b\,c\:d\\e");
+        assert_eq!(
+            wifi_join_payload("Ada;-WiLi", r"pa:ss,wo\rd"),
+            r"WIFI:S:Ada\;-WiLi;T:WPA;P:pa\:ss\,wo\\rd;;"
+        );
+    }
+}
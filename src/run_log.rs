@@ -0,0 +1,200 @@
+// run_log.rs
+//
+// One JSON-lines file per configuration run, kept under the user's data folder so
+// the details survive after the Summary console is cleared. Every line is an
+// object with `ts_ms` (Unix time), `elapsed_ms` (since the run started) and
+// `event`; the other fields depend on the event.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{Value, json};
+
+use crate::config_error::{ConfigError, ConfigFailure};
+use crate::pipeline::ConfigJob;
+
+/// Run logs kept before the oldest are deleted.
+const MAX_RUN_LOGS: usize = 50;
+
+struct RunLog {
+    file: fs::File,
+    started: Instant,
+}
+
+/// The run being logged. Configuration runs never overlap, so one is enough.
+static CURRENT_RUN: Mutex<Option<RunLog>> = Mutex::new(None);
+
+pub fn logs_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("build_a_badge")
+        .join("logs")
+}
+
+fn unix_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_millis())
+        .unwrap_or(0)
+}
+
+/// USB serial devices plugged in right now, so a log shows which badge was used.
+fn connected_devices() -> Vec<Value> {
+    serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|info| match info.port_type {
+            serialport::SerialPortType::UsbPort(usb) => Some(json!({
+                "port": info.port_name,
+                "vid": format!("{:04x}", usb.vid),
+                "pid": format!("{:04x}", usb.pid),
+                "serial_number": usb.serial_number,
+                "product": usb.product,
+            })),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Opens a new log file for `job` and makes it the current run.
+pub fn start_run(job: &ConfigJob) -> Result<PathBuf, String> {
+    let dir = logs_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    prune_old_logs(&dir);
+
+    let path = dir.join(format!("run-{}.jsonl", unix_millis()));
+    let file = fs::File::create(&path)
+        .map_err(|e| format!("Could not create {}: {}", path.display(), e))?;
+    *CURRENT_RUN.lock().unwrap_or_else(|e| e.into_inner()) = Some(RunLog {
+        file,
+        started: Instant::now(),
+    });
+
+    record(
+        "run_started",
+        json!({
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "os": std::env::consts::OS,
+            "badge_name": job.badge_name,
+            "led_mode": job.led_mode.map(|mode| mode.display_name()),
+            "image": job.image_fwi_path.display().to_string(),
+            "wasm_app": job.wasm_app.local_path.display().to_string(),
+            "devices": connected_devices(),
+        }),
+    );
+    println!("Run log: Writing {}", path.display());
+    Ok(path)
+}
+
+/// Appends an event to the current run. Does nothing between runs.
+pub fn record(event: &str, fields: Value) {
+    let mut current = CURRENT_RUN.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(run) = current.as_mut() else {
+        return;
+    };
+
+    let mut line = json!({
+        "ts_ms": unix_millis(),
+        "elapsed_ms": run.started.elapsed().as_millis(),
+        "event": event,
+    });
+    if let (Some(line), Value::Object(fields)) = (line.as_object_mut(), fields) {
+        line.extend(fields);
+    }
+
+    // A log that can't be written shouldn't stop the badge from being configured
+    if let Err(e) = writeln!(run.file, "{}", line) {
+        println!("Run log: Could not write event {}: {}", event, e);
+    }
+}
+
+/// Records one fwi-serial invocation. `exit_code` is `None` when it never finished.
+pub fn record_command(
+    args: &[String],
+    duration: Duration,
+    exit_code: Option<i32>,
+    stdout: &str,
+    stderr: &str,
+) {
+    record(
+        "command",
+        json!({
+            "args": args,
+            "duration_ms": duration.as_millis(),
+            "exit_code": exit_code,
+            "stdout": stdout,
+            "stderr": stderr,
+        }),
+    );
+}
+
+pub fn record_step(step: &str, duration: Duration, result: &Result<String, ConfigError>) {
+    let fields = match result {
+        Ok(output) => json!({
+            "step": step,
+            "duration_ms": duration.as_millis(),
+            "ok": true,
+            "output": output,
+        }),
+        Err(error) => json!({
+            "step": step,
+            "duration_ms": duration.as_millis(),
+            "ok": false,
+            "error_kind": error.title(),
+            "error": error.detail(),
+        }),
+    };
+    record("step", fields);
+}
+
+/// Writes the outcome and closes the current run.
+pub fn finish_run(result: &Result<String, ConfigFailure>) {
+    let failure = result.as_ref().err();
+    record(
+        "run_finished",
+        json!({
+            "ok": result.is_ok(),
+            "failed_step": failure.map(|failure| failure.step),
+            "error_kind": failure.map(|failure| failure.error.title()),
+        }),
+    );
+    *CURRENT_RUN.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn prune_old_logs(dir: &Path) {
+    let mut logs: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    // Names are Unix times, so sorting by name sorts by age
+    logs.sort();
+    let excess = (logs.len() + 1).saturating_sub(MAX_RUN_LOGS);
+    for path in logs.into_iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Shows `logs_dir()` in the system file manager.
+pub fn open_logs_folder() -> Result<(), String> {
+    let dir = logs_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+
+    let opener = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener)
+        .arg(&dir)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Could not open {}: {}", dir.display(), e))
+}
@@ -0,0 +1,204 @@
+// serial_console.rs
+//
+// Live connection to the badge's serial port for the Console screen. The port is
+// read on a plain thread (serialport is blocking) and bridged into an iced
+// subscription; writes go back through a channel handed to the app on connect.
+
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use futures::SinkExt;
+use iced::Subscription;
+use tokio::sync::mpsc;
+
+pub const DEFAULT_BAUD_RATE: u32 = 115_200;
+
+/// Bytes of scrollback kept in memory; older output is dropped from the front.
+pub const SCROLLBACK_BYTES: usize = 256 * 1024;
+
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// USB vendor and product IDs a FreeWili badge's serial port enumerates with:
+/// Intrepid Control Systems' main and display processors, and the RP2040 CDC
+/// port older firmware reports.
+const BADGE_USB_IDS: &[(u16, u16)] = &[(0x093C, 0x2054), (0x093C, 0x2055), (0x2E8A, 0x000A)];
+
+/// Sends bytes to the connected port. Cheap to clone into messages.
+#[derive(Debug, Clone)]
+pub struct ConsoleWriter(mpsc::UnboundedSender<Vec<u8>>);
+
+impl ConsoleWriter {
+    pub fn send(&self, bytes: Vec<u8>) -> Result<(), String> {
+        self.0
+            .send(bytes)
+            .map_err(|_| "The serial port is closed".to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ConsoleEvent {
+    Connected(ConsoleWriter),
+    Data(Vec<u8>),
+    Disconnected(String),
+}
+
+/// A serial port the console can connect to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortChoice {
+    pub name: String,
+    pub label: String,
+    /// The port belongs to a FreeWili badge, going by its USB IDs.
+    pub is_badge: bool,
+}
+
+impl std::fmt::Display for PortChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+/// Serial ports present right now, badges first, then other USB devices.
+pub fn available_ports() -> Vec<PortChoice> {
+    let mut ports: Vec<((bool, bool), PortChoice)> = serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|info| match info.port_type {
+            serialport::SerialPortType::UsbPort(usb) => {
+                let is_badge = BADGE_USB_IDS.contains(&(usb.vid, usb.pid));
+                let product = usb.product.unwrap_or_else(|| "USB serial".to_string());
+                let label = format!("{} ({})", info.port_name, product);
+                (
+                    (is_badge, true),
+                    PortChoice {
+                        name: info.port_name,
+                        label,
+                        is_badge,
+                    },
+                )
+            }
+            _ => (
+                (false, false),
+                PortChoice {
+                    label: info.port_name.clone(),
+                    name: info.port_name,
+                    is_badge: false,
+                },
+            ),
+        })
+        .collect();
+
+    ports.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+    ports.into_iter().map(|(_, port)| port).collect()
+}
+
+/// Keeps `port_name` open for as long as the subscription is alive.
+pub fn connect(port_name: String, baud_rate: u32) -> Subscription<ConsoleEvent> {
+    iced::subscription::channel(
+        (
+            std::any::TypeId::of::<ConsoleWriter>(),
+            port_name.clone(),
+            baud_rate,
+        ),
+        100,
+        move |mut output| async move {
+            let port = serialport::new(&port_name, baud_rate)
+                .timeout(READ_TIMEOUT)
+                .open();
+
+            let (mut writer, mut reader) = match port.and_then(|port| Ok((port.try_clone()?, port)))
+            {
+                Ok(pair) => pair,
+                Err(e) => {
+                    println!("Console: Could not open {}: {}", port_name, e);
+                    let _ = output
+                        .send(ConsoleEvent::Disconnected(format!(
+                            "Could not open {}: {}",
+                            port_name, e
+                        )))
+                        .await;
+                    return futures::future::pending().await;
+                }
+            };
+            println!("Console: Connected to {} at {} baud", port_name, baud_rate);
+
+            let (write_tx, mut write_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+            let _ = output
+                .send(ConsoleEvent::Connected(ConsoleWriter(write_tx)))
+                .await;
+
+            // The reader thread ends on its own once this future is dropped and `data_rx` with it
+            let (data_tx, mut data_rx) = mpsc::unbounded_channel::<Result<Vec<u8>, String>>();
+            std::thread::spawn(move || {
+                let mut buffer = [0u8; 1024];
+                loop {
+                    let result = match reader.read(&mut buffer) {
+                        Ok(0) => continue,
+                        Ok(count) => Ok(buffer[..count].to_vec()),
+                        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                            if data_tx.is_closed() {
+                                break;
+                            }
+                            continue;
+                        }
+                        Err(e) => Err(e.to_string()),
+                    };
+                    let is_error = result.is_err();
+                    if data_tx.send(result).is_err() || is_error {
+                        break;
+                    }
+                }
+            });
+
+            loop {
+                tokio::select! {
+                    Some(bytes) = write_rx.recv() => {
+                        if let Err(e) = writer.write_all(&bytes).and_then(|()| writer.flush()) {
+                            let _ = output
+                                .send(ConsoleEvent::Disconnected(format!("Write failed: {}", e)))
+                                .await;
+                            break;
+                        }
+                    }
+                    received = data_rx.recv() => {
+                        match received {
+                            Some(Ok(bytes)) => {
+                                let _ = output.send(ConsoleEvent::Data(bytes)).await;
+                            }
+                            Some(Err(error)) => {
+                                let _ = output.send(ConsoleEvent::Disconnected(error)).await;
+                                break;
+                            }
+                            None => break,
+                        }
+                    }
+                }
+            }
+
+            println!("Console: Disconnected from {}", port_name);
+            futures::future::pending().await
+        },
+    )
+}
+
+/// Classic 16-bytes-per-row hex dump with an ASCII gutter.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{:08x}  {:<47}  {}", row * 16, hex.join(" "), ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
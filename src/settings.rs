@@ -0,0 +1,395 @@
+// settings.rs
+//
+// Typed model of the badge's `/settings.txt`. The wizard used to write a fixed
+// template; everything the badge firmware reads from that file now lives here so
+// the Advanced Settings screen can edit it and the pipeline can serialise it.
+
+use crate::wasm_apps::{self, BUNDLED_APP_FILE};
+use crate::{app_config, validation};
+
+/// Quick starting points for the Advanced Settings screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsPreset {
+    Default,
+    SecuredWifi,
+    WifiOff,
+    BluetoothOnly,
+    RadiosOff,
+}
+
+impl SettingsPreset {
+    pub const ALL: [SettingsPreset; 5] = [
+        SettingsPreset::Default,
+        SettingsPreset::SecuredWifi,
+        SettingsPreset::WifiOff,
+        SettingsPreset::BluetoothOnly,
+        SettingsPreset::RadiosOff,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SettingsPreset::Default => "Workshop default (Wi-Fi + Bluetooth)",
+            SettingsPreset::SecuredWifi => "Secured Wi-Fi (WPA2 passphrase)",
+            SettingsPreset::WifiOff => "Wi-Fi off",
+            SettingsPreset::BluetoothOnly => "Bluetooth only",
+            SettingsPreset::RadiosOff => "All radios off",
+        }
+    }
+}
+
+/// Boolean keys in `settings.txt`, used to route checkbox toggles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsFlag {
+    WifiApEnabled,
+    WifiApAuth,
+    BluetoothEnabled,
+    BluetoothTerminal,
+    ServerStart,
+    OrcaBottlenoseComm,
+}
+
+impl SettingsFlag {
+    pub const ALL: [SettingsFlag; 6] = [
+        SettingsFlag::WifiApEnabled,
+        SettingsFlag::WifiApAuth,
+        SettingsFlag::BluetoothEnabled,
+        SettingsFlag::BluetoothTerminal,
+        SettingsFlag::ServerStart,
+        SettingsFlag::OrcaBottlenoseComm,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SettingsFlag::WifiApEnabled => "Wi-Fi access point",
+            SettingsFlag::WifiApAuth => "Wi-Fi authentication",
+            SettingsFlag::BluetoothEnabled => "Bluetooth",
+            SettingsFlag::BluetoothTerminal => "Bluetooth terminal",
+            SettingsFlag::ServerStart => "Web server",
+            SettingsFlag::OrcaBottlenoseComm => "Orca/Bottlenose comms",
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            SettingsFlag::WifiApEnabled => "wifiAPEn",
+            SettingsFlag::WifiApAuth => "wifiAPAuth",
+            SettingsFlag::BluetoothEnabled => "btEn",
+            SettingsFlag::BluetoothTerminal => "btTerm",
+            SettingsFlag::ServerStart => "serverStart",
+            SettingsFlag::OrcaBottlenoseComm => "orcaBottlenoseComm",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceSettings {
+    pub wifi_ap_enabled: bool,
+    /// Empty means "derive from the badge name" (`<name>-WiLi`).
+    pub wifi_ap_ssid: String,
+    pub wifi_ap_auth: bool,
+    /// WPA2 passphrase written when `wifi_ap_auth` is on.
+    pub wifi_ap_passphrase: String,
+    pub bluetooth_enabled: bool,
+    /// Empty means "derive from the badge name" (`<name>-WiLi`).
+    pub bluetooth_name: String,
+    pub bluetooth_terminal: bool,
+    pub server_start: bool,
+    pub orca_bottlenose_comm: bool,
+    pub default_script: String,
+}
+
+// WPA2-PSK passphrases are 8 to 63 printable ASCII characters.
+const MIN_PASSPHRASE_LEN: usize = 8;
+const MAX_PASSPHRASE_LEN: usize = 63;
+const GENERATED_PASSPHRASE_LEN: usize = 16;
+
+// No 0/O, 1/l/I: the passphrase gets read off a screen and typed on a phone.
+const PASSPHRASE_ALPHABET: &[u8] = b"abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Generates a random WPA2 passphrase that is easy to read aloud.
+pub fn generate_passphrase() -> String {
+    use rand::Rng;
+
+    let mut rng = rand::thread_rng();
+    (0..GENERATED_PASSPHRASE_LEN)
+        .map(|_| PASSPHRASE_ALPHABET[rng.gen_range(0..PASSPHRASE_ALPHABET.len())] as char)
+        .collect()
+}
+
+impl Default for DeviceSettings {
+    fn default() -> Self {
+        Self {
+            wifi_ap_enabled: true,
+            wifi_ap_ssid: String::new(),
+            wifi_ap_auth: false,
+            wifi_ap_passphrase: String::new(),
+            bluetooth_enabled: true,
+            bluetooth_name: String::new(),
+            bluetooth_terminal: true,
+            server_start: true,
+            orca_bottlenose_comm: true,
+            default_script: app_config::get()
+                .remote_paths
+                .script(BUNDLED_APP_FILE),
+        }
+    }
+}
+
+impl DeviceSettings {
+    /// Applies the radio flags of a preset, keeping names, passphrase and the default script.
+    /// "Wi-Fi off" leaves Bluetooth as it was.
+    pub fn apply_preset(&mut self, preset: SettingsPreset) {
+        let (wifi, bluetooth) = match preset {
+            SettingsPreset::Default | SettingsPreset::SecuredWifi => (true, Some(true)),
+            SettingsPreset::WifiOff => (false, None),
+            SettingsPreset::BluetoothOnly => (false, Some(true)),
+            SettingsPreset::RadiosOff => (false, Some(false)),
+        };
+
+        self.wifi_ap_enabled = wifi;
+        self.wifi_ap_auth = preset == SettingsPreset::SecuredWifi;
+        self.server_start = wifi;
+        self.orca_bottlenose_comm = wifi;
+        if let Some(bluetooth) = bluetooth {
+            self.bluetooth_enabled = bluetooth;
+            self.bluetooth_terminal = bluetooth;
+        }
+    }
+
+    /// The preset these settings currently match, if any. "Wi-Fi off" matches
+    /// whatever Bluetooth is doing, so the presets that also pin Bluetooth win.
+    pub fn matching_preset(&self) -> Option<SettingsPreset> {
+        SettingsPreset::ALL
+            .into_iter()
+            .filter(|preset| *preset != SettingsPreset::WifiOff)
+            .chain([SettingsPreset::WifiOff])
+            .find(|preset| {
+                let mut candidate = self.clone();
+                candidate.apply_preset(*preset);
+                candidate == *self
+            })
+    }
+
+    pub fn flag(&self, flag: SettingsFlag) -> bool {
+        match flag {
+            SettingsFlag::WifiApEnabled => self.wifi_ap_enabled,
+            SettingsFlag::WifiApAuth => self.wifi_ap_auth,
+            SettingsFlag::BluetoothEnabled => self.bluetooth_enabled,
+            SettingsFlag::BluetoothTerminal => self.bluetooth_terminal,
+            SettingsFlag::ServerStart => self.server_start,
+            SettingsFlag::OrcaBottlenoseComm => self.orca_bottlenose_comm,
+        }
+    }
+
+    pub fn set_flag(&mut self, flag: SettingsFlag, value: bool) {
+        match flag {
+            SettingsFlag::WifiApEnabled => self.wifi_ap_enabled = value,
+            SettingsFlag::WifiApAuth => self.wifi_ap_auth = value,
+            SettingsFlag::BluetoothEnabled => self.bluetooth_enabled = value,
+            SettingsFlag::BluetoothTerminal => self.bluetooth_terminal = value,
+            SettingsFlag::ServerStart => self.server_start = value,
+            SettingsFlag::OrcaBottlenoseComm => self.orca_bottlenose_comm = value,
+        }
+    }
+
+    pub fn effective_ssid(&self, badge_name: &str) -> String {
+        if self.wifi_ap_ssid.is_empty() {
+            format!("{badge_name}{}", validation::NAME_SUFFIX)
+        } else {
+            self.wifi_ap_ssid.clone()
+        }
+    }
+
+    pub fn effective_bluetooth_name(&self, badge_name: &str) -> String {
+        if self.bluetooth_name.is_empty() {
+            format!("{badge_name}{}", validation::NAME_SUFFIX)
+        } else {
+            self.bluetooth_name.clone()
+        }
+    }
+
+    /// Whether the access point will come up with WPA2 authentication.
+    pub fn is_wifi_secured(&self) -> bool {
+        self.wifi_ap_enabled && self.wifi_ap_auth
+    }
+
+    /// Returns a human readable message for every setting that would produce a broken badge.
+    pub fn validate(&self, badge_name: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.wifi_ap_enabled {
+            let ssid = self.effective_ssid(badge_name);
+            errors.extend(validation::validate_ssid(&ssid).iter().map(ToString::to_string));
+        }
+        if self.bluetooth_enabled {
+            let bluetooth_name = self.effective_bluetooth_name(badge_name);
+            errors.extend(
+                validation::validate_bluetooth_name(&bluetooth_name)
+                    .iter()
+                    .map(ToString::to_string),
+            );
+        }
+
+        if self.is_wifi_secured() {
+            let passphrase = &self.wifi_ap_passphrase;
+            if passphrase.len() < MIN_PASSPHRASE_LEN || passphrase.len() > MAX_PASSPHRASE_LEN {
+                errors.push(format!(
+                    "Wi-Fi passphrase must be {} to {} characters",
+                    MIN_PASSPHRASE_LEN, MAX_PASSPHRASE_LEN
+                ));
+            }
+            if !passphrase.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+                errors.push("Wi-Fi passphrase must be printable ASCII".to_string());
+            }
+        }
+
+        for (label, value) in [
+            ("Wi-Fi name", &self.wifi_ap_ssid),
+            ("Wi-Fi passphrase", &self.wifi_ap_passphrase),
+            ("Bluetooth name", &self.bluetooth_name),
+            ("Default script", &self.default_script),
+        ] {
+            if value.contains(['\n', '\r', '=']) {
+                errors.push(format!("{label} must not contain line breaks or '='"));
+            }
+        }
+
+        if !self.default_script.is_empty() {
+            if !self.default_script.starts_with('/') {
+                errors.push("Default script must be an absolute path on the badge".to_string());
+            }
+            if !wasm_apps::is_wasm_file_name(&self.default_script) {
+                errors.push("Default script must be a .wasm file".to_string());
+            }
+        }
+
+        if self.server_start && !self.wifi_ap_enabled {
+            errors.push("The web server needs the Wi-Fi access point enabled".to_string());
+        }
+        if self.bluetooth_terminal && !self.bluetooth_enabled {
+            errors.push("The Bluetooth terminal needs Bluetooth enabled".to_string());
+        }
+
+        errors
+    }
+
+    /// Parses a `settings.txt` read back from a badge. Unknown keys are ignored and
+    /// missing ones keep their defaults. Names that match the `<name>-WiLi`
+    /// convention for `badge_name` are left empty so they keep following the name.
+    pub fn parse(contents: &str, badge_name: &str) -> Self {
+        let mut settings = Self::default();
+
+        for line in contents.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            if let Some(flag) = SettingsFlag::ALL.into_iter().find(|flag| flag.key() == key) {
+                settings.set_flag(flag, value == "1");
+                continue;
+            }
+
+            match key {
+                "wifiAPssid" => settings.wifi_ap_ssid = value.to_string(),
+                "wifiAPpass" => settings.wifi_ap_passphrase = value.to_string(),
+                "btAPen" => settings.bluetooth_name = value.to_string(),
+                "defscript" => settings.default_script = value.to_string(),
+                _ => {}
+            }
+        }
+
+        let derived_name = format!("{badge_name}{}", validation::NAME_SUFFIX);
+        if settings.wifi_ap_ssid == derived_name {
+            settings.wifi_ap_ssid.clear();
+        }
+        if settings.bluetooth_name == derived_name {
+            settings.bluetooth_name.clear();
+        }
+
+        settings
+    }
+
+    /// Renders the `settings.txt` file uploaded to the badge.
+    pub fn to_settings_txt(&self, badge_name: &str) -> String {
+        let flag = |value: bool| if value { 1 } else { 0 };
+
+        format!(
+            "wifiAPEn={}
+wifiAPssid={}
+wifiAPAuth={}
+{}btEn={}
+btAPen={}
+btTerm={}
+serverStart={}
+orcaBottlenoseComm={}
+defscript={}\n",
+            flag(self.wifi_ap_enabled),
+            self.effective_ssid(badge_name),
+            // An access point that's off has no authentication to configure
+            flag(self.is_wifi_secured()),
+            // `wifiAPpass` is the Free-WiLi firmware's passphrase key, read next to
+            // `wifiAPAuth`; the stock settings.txt leaves it out because its AP is open
+            if self.is_wifi_secured() {
+                format!("wifiAPpass={}\n", self.wifi_ap_passphrase)
+            } else {
+                String::new()
+            },
+            flag(self.bluetooth_enabled),
+            self.effective_bluetooth_name(badge_name),
+            flag(self.bluetooth_terminal),
+            flag(self.server_start),
+            flag(self.orca_bottlenose_comm),
+            self.default_script,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_preset_survives_settings_txt() {
+        for preset in SettingsPreset::ALL {
+            let mut settings = DeviceSettings::default();
+            settings.apply_preset(preset);
+            if settings.is_wifi_secured() {
+                settings.wifi_ap_passphrase = "correct-horse-42".to_string();
+            }
+
+            let parsed = DeviceSettings::parse(&settings.to_settings_txt("Ada"), "Ada");
+            assert_eq!(parsed, settings, "{:?}", preset);
+            assert!(parsed.matching_preset().is_some(), "{:?}", preset);
+        }
+    }
+
+    #[test]
+    fn wifi_off_is_its_own_preset_when_bluetooth_is_mixed() {
+        let mut settings = DeviceSettings {
+            bluetooth_terminal: false,
+            ..DeviceSettings::default()
+        };
+        settings.apply_preset(SettingsPreset::WifiOff);
+        assert_eq!(settings.matching_preset(), Some(SettingsPreset::WifiOff));
+        assert!(settings.bluetooth_enabled);
+    }
+
+    #[test]
+    fn derived_names_stay_implicit() {
+        let settings = DeviceSettings::default();
+        let settings_txt = settings.to_settings_txt("Ada");
+        assert!(settings_txt.contains("wifiAPssid=Ada-WiLi\n"));
+        assert!(settings_txt.contains("btAPen=Ada-WiLi\n"));
+
+        let parsed = DeviceSettings::parse(&settings_txt, "Ada");
+        assert!(parsed.wifi_ap_ssid.is_empty());
+        assert!(parsed.bluetooth_name.is_empty());
+        assert_eq!(parsed.effective_ssid("Grace"), "Grace-WiLi");
+
+        // A name that only matches some other badge's convention is kept
+        let parsed = DeviceSettings::parse(&settings_txt, "Grace");
+        assert_eq!(parsed.wifi_ap_ssid, "Ada-WiLi");
+        assert_eq!(parsed.bluetooth_name, "Ada-WiLi");
+    }
+}
@@ -0,0 +1,407 @@
+// theme_pack.rs
+//
+// Event branding: the logo, colors, welcome text, default LED mode and picture
+// gallery. The ICS Village look is compiled in; other events drop a folder into
+// `packs_dir()` (or point `theme_pack` in the app config at one) and pick it on
+// the Welcome screen, no rebuild needed. A pack folder holds:
+//
+//   theme.toml      see `Manifest`
+//   *.png, *.jpg    the logo and gallery pictures, named in theme.toml
+//   *.fwi           each gallery picture as uploaded to the badge
+//   *.png           frames and stickers, with alpha (see `layers.rs`)
+//
+// Anything theme.toml leaves out comes from the compiled-in pack.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use iced::Color;
+use iced::widget::image;
+use serde::Deserialize;
+
+use crate::layers::LayerKind;
+use crate::{LedMode, app_config};
+
+const MANIFEST_FILE: &str = "theme.toml";
+
+/// A picture on the Customize Badge screen.
+#[derive(Debug, Clone)]
+pub struct GalleryImage {
+    /// Asset name, as used in the app config and saved designs.
+    pub name: String,
+    /// Identifies the picture in messages and the wizard's state. It's never
+    /// drawn: `assets::picture(&source_path)` is the decoded picture.
+    pub handle: image::Handle,
+    /// What gets uploaded.
+    pub fwi_path: PathBuf,
+    /// The picture the `.fwi` was made from.
+    pub source_path: PathBuf,
+}
+
+/// A frame or sticker that can be layered over the picture.
+#[derive(Debug, Clone)]
+pub struct Decoration {
+    /// As saved in a design's layers.
+    pub name: String,
+    pub handle: image::Handle,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThemePack {
+    pub name: String,
+    /// Folder the pack was loaded from; `None` for the compiled-in pack.
+    pub dir: Option<PathBuf>,
+    /// `None` for the wizard's own logo. Either way it's drawn through `assets.rs`.
+    pub logo: Option<PathBuf>,
+    /// Buttons and selection highlights.
+    pub accent: Color,
+    /// Headings and status messages.
+    pub text: Color,
+    /// Paragraphs on the Welcome screen.
+    pub welcome_text: Vec<String>,
+    pub default_led_mode: Option<LedMode>,
+    /// Gallery picture picked on start, by name.
+    pub default_image: Option<String>,
+    pub gallery: Vec<GalleryImage>,
+    pub frames: Vec<Decoration>,
+    pub stickers: Vec<Decoration>,
+}
+
+impl ThemePack {
+    /// The logo and gallery pictures, for `assets::decode_pictures`.
+    pub fn pictures(&self) -> Vec<PathBuf> {
+        self.logo
+            .iter()
+            .chain(self.gallery.iter().map(|gallery| &gallery.source_path))
+            .cloned()
+            .collect()
+    }
+
+    /// The gallery entry showing `handle`.
+    pub fn find(&self, handle: &image::Handle) -> Option<&GalleryImage> {
+        self.gallery
+            .iter()
+            .find(|gallery| gallery.handle == *handle)
+    }
+
+    pub fn find_named(&self, name: &str) -> Option<&GalleryImage> {
+        self.gallery
+            .iter()
+            .find(|gallery| gallery.name == name.trim())
+    }
+
+    pub fn decorations(&self, kind: LayerKind) -> &[Decoration] {
+        match kind {
+            LayerKind::Frame => &self.frames,
+            LayerKind::Sticker => &self.stickers,
+        }
+    }
+
+    pub fn find_decoration(&self, kind: LayerKind, name: &str) -> Option<&Decoration> {
+        self.decorations(kind)
+            .iter()
+            .find(|decoration| decoration.name == name)
+    }
+}
+
+/// theme.toml.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Manifest {
+    name: String,
+    /// Image file in the pack folder.
+    logo: Option<String>,
+    welcome_text: Vec<String>,
+    /// By name ("Rainbow Chase") or number (4).
+    default_led_mode: Option<String>,
+    default_image: Option<String>,
+    colors: ManifestColors,
+    gallery: Vec<ManifestImage>,
+    frames: Vec<ManifestDecoration>,
+    stickers: Vec<ManifestDecoration>,
+}
+
+/// `"#rrggbb"` colors.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ManifestColors {
+    accent: Option<String>,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestImage {
+    name: String,
+    image: String,
+    fwi: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestDecoration {
+    name: String,
+    image: String,
+}
+
+/// Built once, so its handles stay equal to the ones the wizard already holds.
+static BUILT_IN: LazyLock<Arc<ThemePack>> = LazyLock::new(|| {
+    let gallery_image = |name: &str, source_path: &str| GalleryImage {
+        name: name.to_string(),
+        handle: image::Handle::from_path(source_path),
+        fwi_path: PathBuf::from(format!("assets/{}.fwi", name)),
+        source_path: PathBuf::from(source_path),
+    };
+
+    Arc::new(ThemePack {
+        name: "ICS Village".to_string(),
+        dir: None,
+        logo: None,
+        accent: Color::from_rgb8(255, 191, 0),
+        text: Color::from_rgb8(0, 85, 150),
+        welcome_text: vec![
+            "Welcome to the Build-A-Badge Workshop, where creativity and fun come alive for all! Dive into a vibrant space that sparks your imagination, encourages discovery, and empowers you to craft a unique DEFCON badge—along with memories to cherish forever.".to_string(),
+            "What's in store? Get ready to hack your ICS Village Badge with a hands-on, interactive experience! You'll create custom script and design your very own application, guided every step of the way by a friendly ICS Village Badge Builder associate.".to_string(),
+        ],
+        default_led_mode: None,
+        default_image: None,
+        gallery: vec![
+            gallery_image("defcon_logo", "assets/defcon_logo.png"),
+            gallery_image("doge", "assets/doge.png"),
+            gallery_image("puppy", "assets/puppy.png"),
+            gallery_image("pip_boy", "assets/pip_boy.jpg"),
+            gallery_image("vegas", "assets/vegas.png"),
+        ],
+        frames: Vec::new(),
+        stickers: Vec::new(),
+    })
+});
+
+static ACTIVE: LazyLock<RwLock<Arc<ThemePack>>> = LazyLock::new(|| RwLock::new(BUILT_IN.clone()));
+
+/// The pack the wizard is currently dressed in.
+pub fn active() -> Arc<ThemePack> {
+    ACTIVE
+        .read()
+        .map(|pack| pack.clone())
+        .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+}
+
+pub fn set_active(pack: Arc<ThemePack>) {
+    println!("Theme pack: Using {}", pack.name);
+    match ACTIVE.write() {
+        Ok(mut active) => *active = pack,
+        Err(poisoned) => *poisoned.into_inner() = pack,
+    }
+}
+
+/// Folder scanned for theme packs, one subfolder each.
+pub fn packs_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("build_a_badge")
+        .join("themes")
+}
+
+/// An entry in the Welcome screen's theme picker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackChoice {
+    pub name: String,
+    /// `None` for the compiled-in pack.
+    pub dir: Option<PathBuf>,
+}
+
+impl fmt::Display for PackChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl PackChoice {
+    pub fn of(pack: &ThemePack) -> Self {
+        Self {
+            name: pack.name.clone(),
+            dir: pack.dir.clone(),
+        }
+    }
+
+    pub fn load(&self) -> Result<Arc<ThemePack>, String> {
+        match &self.dir {
+            Some(dir) => load(dir).map(Arc::new),
+            None => Ok(BUILT_IN.clone()),
+        }
+    }
+}
+
+/// The compiled-in pack, every pack in `packs_dir()` and the configured one.
+pub fn available() -> Vec<PackChoice> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(packs_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(MANIFEST_FILE).is_file())
+        .collect();
+    dirs.sort();
+    if let Some(configured) = configured_dir()
+        && !dirs.contains(&configured)
+    {
+        dirs.push(configured);
+    }
+
+    let mut choices = vec![PackChoice::of(&BUILT_IN)];
+    choices.extend(dirs.into_iter().filter_map(|dir| {
+        let manifest = read_manifest(&dir).ok()?;
+        Some(PackChoice {
+            name: pack_name(&manifest, &dir),
+            dir: Some(dir),
+        })
+    }));
+    choices
+}
+
+/// `theme_pack` from the app config: a folder in `packs_dir()` or a path.
+fn configured_dir() -> Option<PathBuf> {
+    let configured = app_config::get().theme_pack.trim();
+    if configured.is_empty() {
+        return None;
+    }
+    let in_packs_dir = packs_dir().join(configured);
+    Some(if in_packs_dir.is_dir() {
+        in_packs_dir
+    } else {
+        PathBuf::from(configured)
+    })
+}
+
+/// Switches to the pack named in the app config, if any. Problems are returned
+/// for the Welcome screen, and the compiled-in pack stays in use.
+pub fn load_configured() -> Result<(), String> {
+    let Some(dir) = configured_dir() else {
+        return Ok(());
+    };
+    let pack = load(&dir)?;
+    set_active(Arc::new(pack));
+    Ok(())
+}
+
+fn read_manifest(dir: &Path) -> Result<Manifest, String> {
+    let path = dir.join(MANIFEST_FILE);
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    toml::from_str(&contents).map_err(|e| format!("{} is not a valid theme: {}", path.display(), e))
+}
+
+fn pack_name(manifest: &Manifest, dir: &Path) -> String {
+    if manifest.name.trim().is_empty() {
+        dir.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| dir.display().to_string())
+    } else {
+        manifest.name.trim().to_string()
+    }
+}
+
+/// Reads the pack in `dir`. Missing pictures are errors rather than blank buttons,
+/// so a broken pack is caught before the booth opens.
+pub fn load(dir: &Path) -> Result<ThemePack, String> {
+    let manifest = read_manifest(dir)?;
+    let built_in = &*BUILT_IN;
+    let existing = |file: &str| {
+        let path = dir.join(file);
+        if path.is_file() {
+            Ok(path)
+        } else {
+            Err(format!("Theme pack {} is missing {}", dir.display(), file))
+        }
+    };
+    let color = |value: &Option<String>, fallback: Color| match value {
+        Some(value) => {
+            parse_color(value).ok_or_else(|| format!("\"{}\" is not a #rrggbb color", value))
+        }
+        None => Ok(fallback),
+    };
+
+    let logo = match &manifest.logo {
+        Some(logo) => Some(existing(logo)?),
+        None => built_in.logo.clone(),
+    };
+    let default_led_mode = match &manifest.default_led_mode {
+        Some(mode) => {
+            Some(LedMode::from_name(mode).ok_or_else(|| format!("Unknown LED mode \"{}\"", mode))?)
+        }
+        None => None,
+    };
+
+    let gallery = if manifest.gallery.is_empty() {
+        built_in.gallery.clone()
+    } else {
+        manifest
+            .gallery
+            .iter()
+            .map(|entry| {
+                let source_path = existing(&entry.image)?;
+                Ok(GalleryImage {
+                    name: entry.name.clone(),
+                    handle: image::Handle::from_path(&source_path),
+                    fwi_path: existing(&entry.fwi)?,
+                    source_path,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?
+    };
+    let decorations = |entries: &[ManifestDecoration]| {
+        entries
+            .iter()
+            .map(|entry| {
+                let path = existing(&entry.image)?;
+                Ok(Decoration {
+                    name: entry.name.clone(),
+                    handle: image::Handle::from_path(&path),
+                    path,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()
+    };
+    let frames = decorations(&manifest.frames)?;
+    let stickers = decorations(&manifest.stickers)?;
+
+    if let Some(name) = &manifest.default_image
+        && !gallery.iter().any(|gallery| gallery.name == *name)
+    {
+        return Err(format!("default_image \"{}\" is not in the gallery", name));
+    }
+
+    Ok(ThemePack {
+        name: pack_name(&manifest, dir),
+        dir: Some(dir.to_path_buf()),
+        logo,
+        accent: color(&manifest.colors.accent, built_in.accent)?,
+        text: color(&manifest.colors.text, built_in.text)?,
+        welcome_text: if manifest.welcome_text.is_empty() {
+            built_in.welcome_text.clone()
+        } else {
+            manifest.welcome_text
+        },
+        default_led_mode,
+        default_image: manifest.default_image,
+        gallery,
+        frames,
+        stickers,
+    })
+}
+
+/// `#rrggbb`, with or without the `#`.
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |range| u8::from_str_radix(hex.get(range)?, 16).ok();
+    Some(Color::from_rgb8(
+        channel(0..2)?,
+        channel(2..4)?,
+        channel(4..6)?,
+    ))
+}
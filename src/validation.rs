@@ -0,0 +1,219 @@
+// validation.rs
+//
+// The badge name ends up in three places on the device: line one of
+// `build_a_badge.txt`, `wifiAPssid` and `btAPen` in `settings.txt`. Each has its
+// own limits, so names are checked here instead of being filtered keystroke by
+// keystroke.
+
+use std::fmt;
+
+/// Characters the user may type, before the `-WiLi` suffix is added.
+pub const MAX_NAME_CHARS: usize = 20;
+
+/// Appended to the badge name for both radios.
+pub const NAME_SUFFIX: &str = "-WiLi";
+
+/// IEEE 802.11 limits an SSID to 32 octets.
+const MAX_SSID_BYTES: usize = 32;
+
+/// A BLE local name must fit in one 31-byte advertising packet next to its
+/// 2-byte header.
+const MAX_BLUETOOTH_NAME_BYTES: usize = 29;
+
+/// Names that would be confused with stock Free-WiLi devices or the wizard's own fallback.
+const RESERVED_NAMES: &[&str] = &["boring", "freewili", "free-wili", "wili", "default", "admin"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameField {
+    BadgeName,
+    Ssid,
+    BluetoothName,
+}
+
+impl NameField {
+    fn display_name(&self) -> &'static str {
+        match self {
+            NameField::BadgeName => "Badge name",
+            NameField::Ssid => "Wi-Fi name",
+            NameField::BluetoothName => "Bluetooth name",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    TooManyCharacters { count: usize, max: usize },
+    TooManyBytes { field: NameField, bytes: usize, max: usize },
+    InvalidCharacter { field: NameField, character: char },
+    Reserved(String),
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::TooManyCharacters { count, max } => {
+                write!(f, "Name is {count} characters long; the limit is {max}")
+            }
+            NameError::TooManyBytes { field, bytes, max } => write!(
+                f,
+                "{} would be {bytes} bytes; the limit is {max}",
+                field.display_name()
+            ),
+            NameError::InvalidCharacter { field, character } => write!(
+                f,
+                "{} can't contain '{}'",
+                field.display_name(),
+                character.escape_default()
+            ),
+            NameError::Reserved(name) => write!(f, "\"{name}\" is reserved, pick another name"),
+        }
+    }
+}
+
+/// Checks what the user typed on the NameBadge screen. An empty name is valid;
+/// the wizard substitutes its own default.
+pub fn validate_badge_name(name: &str) -> Vec<NameError> {
+    let mut errors = Vec::new();
+
+    let count = name.chars().count();
+    if count > MAX_NAME_CHARS {
+        errors.push(NameError::TooManyCharacters {
+            count,
+            max: MAX_NAME_CHARS,
+        });
+    }
+
+    // Reported once per distinct character so a pasted emoji string doesn't flood the screen
+    let mut seen = Vec::new();
+    for character in name.chars() {
+        if !character.is_ascii_alphanumeric() && !seen.contains(&character) {
+            seen.push(character);
+            errors.push(NameError::InvalidCharacter {
+                field: NameField::BadgeName,
+                character,
+            });
+        }
+    }
+
+    if RESERVED_NAMES.contains(&name.to_ascii_lowercase().as_str()) {
+        errors.push(NameError::Reserved(name.to_string()));
+    }
+
+    // Character problems were already reported against the badge name itself
+    if !name.is_empty() {
+        let full_name = format!("{name}{NAME_SUFFIX}");
+        errors.extend(
+            validate_ssid(&full_name)
+                .into_iter()
+                .chain(validate_bluetooth_name(&full_name))
+                .filter(|error| matches!(error, NameError::TooManyBytes { .. })),
+        );
+    }
+
+    errors
+}
+
+/// Checks a complete access point name as written to `wifiAPssid`.
+pub fn validate_ssid(ssid: &str) -> Vec<NameError> {
+    let mut errors = Vec::new();
+
+    if ssid.len() > MAX_SSID_BYTES {
+        errors.push(NameError::TooManyBytes {
+            field: NameField::Ssid,
+            bytes: ssid.len(),
+            max: MAX_SSID_BYTES,
+        });
+    }
+
+    // Printable ASCII minus the characters many client configuration tools choke on
+    if let Some(character) = ssid
+        .chars()
+        .find(|c| (!c.is_ascii_graphic() && *c != ' ') || matches!(c, '"' | '+' | ']' | '/' | '\\'))
+    {
+        errors.push(NameError::InvalidCharacter {
+            field: NameField::Ssid,
+            character,
+        });
+    }
+
+    errors
+}
+
+/// Checks a complete Bluetooth device name as written to `btAPen`.
+pub fn validate_bluetooth_name(name: &str) -> Vec<NameError> {
+    let mut errors = Vec::new();
+
+    if name.len() > MAX_BLUETOOTH_NAME_BYTES {
+        errors.push(NameError::TooManyBytes {
+            field: NameField::BluetoothName,
+            bytes: name.len(),
+            max: MAX_BLUETOOTH_NAME_BYTES,
+        });
+    }
+
+    if let Some(character) = name.chars().find(|c| !c.is_ascii_graphic() && *c != ' ') {
+        errors.push(NameError::InvalidCharacter {
+            field: NameField::BluetoothName,
+            character,
+        });
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_byte_name_counts_bytes_not_characters() {
+        let name = "é".repeat(15);
+        assert_eq!(
+            validate_badge_name(&name),
+            vec![
+                NameError::InvalidCharacter {
+                    field: NameField::BadgeName,
+                    character: 'é',
+                },
+                NameError::TooManyBytes {
+                    field: NameField::Ssid,
+                    bytes: 35,
+                    max: MAX_SSID_BYTES,
+                },
+                NameError::TooManyBytes {
+                    field: NameField::BluetoothName,
+                    bytes: 35,
+                    max: MAX_BLUETOOTH_NAME_BYTES,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bluetooth_name_fits_29_bytes_with_suffix() {
+        let fits = format!("{}{NAME_SUFFIX}", "a".repeat(24));
+        assert!(validate_bluetooth_name(&fits).is_empty());
+        assert!(validate_ssid(&fits).is_empty());
+
+        let too_long = format!("{}{NAME_SUFFIX}", "a".repeat(25));
+        assert_eq!(
+            validate_bluetooth_name(&too_long),
+            vec![NameError::TooManyBytes {
+                field: NameField::BluetoothName,
+                bytes: 30,
+                max: MAX_BLUETOOTH_NAME_BYTES,
+            }]
+        );
+        assert!(validate_ssid(&too_long).is_empty());
+    }
+
+    #[test]
+    fn reserved_names_ignore_case() {
+        assert_eq!(
+            validate_badge_name("FreeWili"),
+            vec![NameError::Reserved("FreeWili".to_string())]
+        );
+        assert!(validate_badge_name("Ada").is_empty());
+        assert!(validate_badge_name("").is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+// wasm_apps.rs
+//
+// The WASM app the badge runs once it's configured. The wizard ships its own
+// apps next to the executable; users can add any other `.wasm` file from disk.
+
+use std::path::{Path, PathBuf};
+
+use crate::app_config;
+
+/// The app shipped with the wizard, which the badge runs by default.
+pub const BUNDLED_APP_FILE: &str = "build_a_badge.wasm";
+
+const WASM_MAGIC: &[u8] = b"\0asm";
+const WASM_EXTENSION: &str = ".wasm";
+
+/// Whether `file_name` has the `.wasm` extension, in any case.
+pub fn is_wasm_file_name(file_name: &str) -> bool {
+    file_name
+        .len()
+        .checked_sub(WASM_EXTENSION.len())
+        .and_then(|start| file_name.get(start..))
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(WASM_EXTENSION))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmApp {
+    pub name: String,
+    pub description: String,
+    /// The module on this computer.
+    pub local_path: PathBuf,
+    pub is_bundled: bool,
+}
+
+impl WasmApp {
+    /// Apps that come with the wizard. The first one is the default.
+    pub fn bundled() -> Vec<WasmApp> {
+        vec![WasmApp {
+            name: "Build-A-Badge".to_string(),
+            description: "Shows your image and name and runs your LED pattern".to_string(),
+            local_path: PathBuf::from(BUNDLED_APP_FILE),
+            is_bundled: true,
+        }]
+    }
+
+    /// Checks a user-picked file is a WASM module with a name the badge can store.
+    pub fn from_file(path: &Path) -> Result<WasmApp, String> {
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format!("{} has no usable file name", path.display()))?;
+
+        if !is_wasm_file_name(file_name) {
+            return Err(format!("{} is not a .wasm file", file_name));
+        }
+        // The name ends up in `defscript=` and on the fwi-serial command line
+        if let Some(character) = file_name
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        {
+            return Err(format!(
+                "{} can't contain '{}'; rename it using letters, numbers, '-' and '_'",
+                file_name,
+                character.escape_default()
+            ));
+        }
+
+        let bytes =
+            std::fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        if !bytes.starts_with(WASM_MAGIC) {
+            return Err(format!("{} is not a WebAssembly module", file_name));
+        }
+
+        Ok(WasmApp {
+            name: file_name[..file_name.len() - WASM_EXTENSION.len()].to_string(),
+            description: path.display().to_string(),
+            local_path: path.to_path_buf(),
+            is_bundled: false,
+        })
+    }
+
+    /// Name of the module on the badge, which is also what `fwi-serial -w` runs.
+    pub fn file_name(&self) -> String {
+        self.local_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    pub fn remote_path(&self) -> String {
+        app_config::get().remote_paths.script(&self.file_name())
+    }
+}
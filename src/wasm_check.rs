@@ -0,0 +1,265 @@
+// wasm_check.rs
+//
+// Looks a WASM app over before it is uploaded. An app importing a host function
+// with the wrong signature makes `fwi-serial -w` fail on the device with little
+// explanation, so imports are compared against the functions in `HOST_API` here
+// first. That list isn't the whole firmware API, so other imports only warn.
+
+use wasmparser::ValType::{F32, I32};
+use wasmparser::{Parser, Payload, TypeRef, ValType, Validator};
+
+use crate::app_config;
+
+/// Entry points the firmware looks for, in order.
+const ENTRY_POINTS: [&str; 2] = ["_start", "main"];
+
+pub struct HostFunction {
+    pub module: &'static str,
+    pub name: &'static str,
+    pub params: &'static [ValType],
+    pub results: &'static [ValType],
+}
+
+const fn host(
+    module: &'static str,
+    name: &'static str,
+    params: &'static [ValType],
+    results: &'static [ValType],
+) -> HostFunction {
+    HostFunction {
+        module,
+        name,
+        params,
+        results,
+    }
+}
+
+/// Host functions whose signatures are known, from the apps the wizard ships.
+/// The firmware provides more than these.
+pub const HOST_API: &[HostFunction] = &[
+    // Runtime hooks from the wasi-libc start-up code
+    host("env", "__main_void", &[], &[I32]),
+    host("env", "__wasm_call_dtors", &[], &[]),
+    host("env", "__wasi_proc_exit", &[I32], &[]),
+    // Display
+    host("wiliwasm", "addPanel", &[I32; 9], &[]),
+    host("wiliwasm", "addControlPictureFromFile", &[I32; 6], &[]),
+    host("wiliwasm", "addControlText", &[I32; 10], &[]),
+    host("wiliwasm", "showPanel", &[I32], &[]),
+    host("wiliwasm", "setControlValueText", &[I32; 3], &[]),
+    host("wiliwasm", "exitToMainAppMenu", &[], &[]),
+    // Files
+    host("wiliwasm", "openFile", &[I32; 2], &[I32]),
+    host("wiliwasm", "readFileLine", &[I32; 3], &[I32]),
+    host("wiliwasm", "closeFile", &[I32], &[I32]),
+    // Everything else
+    host("wiliwasm", "printInt", &[I32; 4], &[]),
+    host("wiliwasm", "setLEDShowMode", &[I32], &[]),
+    host("wiliwasm", "waitms", &[I32], &[]),
+    host("wiliwasm", "hasEvent", &[], &[I32]),
+    host("wiliwasm", "getEventData", &[I32], &[I32]),
+    host(
+        "wiliwasm",
+        "playSoundFromFrequencyAndDuration",
+        &[F32, F32, F32, I32],
+        &[],
+    ),
+];
+
+/// What was found in a module. Errors mean the badge can't run it; warnings are
+/// things the wizard can't vouch for.
+#[derive(Debug, Clone, Default)]
+pub struct WasmReport {
+    pub file_name: String,
+    pub size: usize,
+    pub imports: usize,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl WasmReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Multi-line report for the configuration console.
+    pub fn to_console_text(&self) -> String {
+        let mut lines = vec![format!(
+            "WASM check for {}: {} KB, {} host imports",
+            self.file_name,
+            self.size.div_ceil(1024),
+            self.imports
+        )];
+        lines.extend(self.errors.iter().map(|error| format!("  ✗ {}", error)));
+        lines.extend(
+            self.warnings
+                .iter()
+                .map(|warning| format!("  ⚠ {}", warning)),
+        );
+        if self.is_ok() {
+            lines.push("✓ WASM module is compatible with the badge".to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+fn signature(params: &[ValType], results: &[ValType]) -> String {
+    let list = |types: &[ValType]| {
+        types
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!("({}) -> ({})", list(params), list(results))
+}
+
+/// Checks `bytes` is a module the badge can link against the host API.
+pub fn check_module(file_name: &str, bytes: &[u8]) -> WasmReport {
+    let mut report = WasmReport {
+        file_name: file_name.to_string(),
+        size: bytes.len(),
+        ..WasmReport::default()
+    };
+
+    // Limits come from the apps known to run, not the firmware, so they only warn
+    let config = app_config::get();
+    if bytes.len() as u64 > config.max_module_kb * 1024 {
+        report.warnings.push(format!(
+            "Module is {} KB; apps over {} KB may not fit on the badge",
+            bytes.len().div_ceil(1024),
+            config.max_module_kb
+        ));
+    }
+
+    // Nothing below is meaningful for a module that doesn't validate
+    if let Err(e) = Validator::new().validate_all(bytes) {
+        report
+            .errors
+            .push(format!("Not a valid WASM module: {}", e));
+        return report;
+    }
+
+    let mut function_types = Vec::new();
+    let mut entry_points = Vec::new();
+    for payload in Parser::new(0).parse_all(bytes) {
+        let Ok(payload) = payload else {
+            break;
+        };
+        match payload {
+            Payload::TypeSection(reader) => {
+                for ty in reader.into_iter_err_on_gc_types().flatten() {
+                    function_types.push(ty);
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader.into_imports().flatten() {
+                    report.imports += 1;
+                    let what = format!("{}::{}", import.module, import.name);
+
+                    let TypeRef::Func(type_index) = import.ty else {
+                        report.errors.push(format!(
+                            "Imports {} as a non-function, which the badge can't provide",
+                            what
+                        ));
+                        continue;
+                    };
+                    let Some(ty) = function_types.get(type_index as usize) else {
+                        continue;
+                    };
+
+                    match HOST_API
+                        .iter()
+                        .find(|host| host.module == import.module && host.name == import.name)
+                    {
+                        None => report.warnings.push(format!(
+                            "Imports {}, which the wizard can't check; make sure the badge firmware provides it",
+                            what
+                        )),
+                        Some(host)
+                            if host.params != ty.params() || host.results != ty.results() =>
+                        {
+                            report.errors.push(format!(
+                                "Imports {} as {}, but the badge provides {}",
+                                what,
+                                signature(ty.params(), ty.results()),
+                                signature(host.params, host.results)
+                            ))
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader.into_iter().flatten() {
+                    if memory.memory64 || memory.shared {
+                        report.errors.push(
+                            "Uses 64-bit or shared memory, which the badge doesn't support"
+                                .to_string(),
+                        );
+                    }
+                    if memory.initial > config.max_memory_pages {
+                        report.warnings.push(format!(
+                            "Starts with {} pages of memory; apps over {} pages may not fit on the badge",
+                            memory.initial, config.max_memory_pages
+                        ));
+                    }
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader.into_iter().flatten() {
+                    if export.kind == wasmparser::ExternalKind::Func
+                        && ENTRY_POINTS.contains(&export.name)
+                    {
+                        entry_points.push(export.name.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if entry_points.is_empty() {
+        report.errors.push(format!(
+            "Exports no entry point; the badge calls {}",
+            ENTRY_POINTS.join(" or ")
+        ));
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (module (import "wiliwasm" "showPanel" (func)) (func (export "_start")))
+    const WRONG_SHOW_PANEL: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+        0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // type 0: () -> ()
+        0x02, 0x16, 0x01, 0x08, b'w', b'i', b'l', b'i', b'w', b'a', b's', b'm', 0x09, b's', b'h',
+        b'o', b'w', b'P', b'a', b'n', b'e', b'l', 0x00, 0x00, // import
+        0x03, 0x02, 0x01, 0x00, // function 1: type 0
+        0x07, 0x0a, 0x01, 0x06, b'_', b's', b't', b'a', b'r', b't', 0x00, 0x01, // export
+        0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b, // empty body
+    ];
+
+    #[test]
+    fn bundled_app_passes() {
+        let report = check_module(
+            "build_a_badge.wasm",
+            include_bytes!("../build_a_badge.wasm"),
+        );
+        assert!(report.is_ok(), "{}", report.to_console_text());
+        assert!(report.warnings.is_empty(), "{}", report.to_console_text());
+    }
+
+    #[test]
+    fn mismatched_host_import_fails() {
+        let report = check_module("wrong.wasm", WRONG_SHOW_PANEL);
+        assert_eq!(
+            report.errors,
+            vec!["Imports wiliwasm::showPanel as () -> (), but the badge provides (i32) -> ()"]
+        );
+    }
+}